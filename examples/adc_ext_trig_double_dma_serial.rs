@@ -16,6 +16,7 @@ extern crate stm32g0;
 extern crate stm32g0xx_hal as hal;
 
 use hal::analog::adc;
+use hal::block;
 use hal::prelude::*;
 use hal::serial::*;
 use hal::stm32;
@@ -27,59 +28,50 @@ use cortex_m::interrupt::Mutex;
 use crate::hal::stm32::{interrupt, Interrupt};
 use hal::analog::adc::{InjTrigSource, Precision, SampleTime}; //, VTemp
 
-use hal::dma::{self, Channel, Target};
+use hal::analog::adc::Adc;
+use hal::dma::{self, CircBuffer};
 
 use crate::hal::analog::adc::DmaMode;
 use crate::hal::analog::adc::InjectMode;
 
-// Make dma globally available
-static G_DMA: Mutex<RefCell<Option<hal::dma::Channels>>> = Mutex::new(RefCell::new(None));
+// Half of the circular buffer filled by the DMA, in samples
+const HALF_SIZE: usize = 2;
 
-const BUFFER_SIZE: u16 = 4;
-// Make the buffer pointer globally available
-static G_DMA_BUFFER_ADDR: Mutex<RefCell<Option<u32>>> = Mutex::new(RefCell::new(None));
+type AdcBuffer = CircBuffer<dma::C1, Adc, u16, HALF_SIZE>;
+type SerialTx = Tx<stm32::USART1, FullConfig>;
+
+// Make the circular buffer and the serial transmitter globally available
+static G_ADC_BUFFER: Mutex<RefCell<Option<AdcBuffer>>> = Mutex::new(RefCell::new(None));
+static G_TX: Mutex<RefCell<Option<SerialTx>>> = Mutex::new(RefCell::new(None));
 
 #[interrupt]
-unsafe fn DMA_CHANNEL1() {
-    static mut DMA: Option<hal::dma::Channels> = None;
-    static mut DMA_BUFFER_ADDR: Option<u32> = None;
+fn DMA_CHANNEL1() {
+    static mut ADC_BUFFER: Option<AdcBuffer> = None;
+    static mut TX: Option<SerialTx> = None;
 
-    let dma_ch = DMA.get_or_insert_with(|| {
+    let adc_buffer = ADC_BUFFER.get_or_insert_with(|| {
         cortex_m::interrupt::free(|cs| {
-            // Move dma here, leaving a None in its place
-            G_DMA.borrow(cs).replace(None).unwrap()
+            // Move the circular buffer here, leaving a None in its place
+            G_ADC_BUFFER.borrow(cs).replace(None).unwrap()
         })
     });
 
-    let dma_buf_addr = DMA_BUFFER_ADDR.get_or_insert_with(|| {
+    let tx = TX.get_or_insert_with(|| {
         cortex_m::interrupt::free(|cs| {
-            // Move dma buffer pointer here, leaving a None in its place
-            G_DMA_BUFFER_ADDR.borrow(cs).replace(None).unwrap()
+            // Move the serial transmitter here, leaving a None in its place
+            G_TX.borrow(cs).replace(None).unwrap()
         })
     });
 
-    let tx_dma_buf_first_addr: u32 = *dma_buf_addr;
-    let tx_dma_buf_second_addr: u32 = *dma_buf_addr + (BUFFER_SIZE) as u32;
-    // Address is in byte, value in 2Bytes, this is why second dma buffer ist added with BUFFER_SIZE
-    // and not BUFFER_SIZE/2
-
-    let dma = &(*hal::stm32::DMA::ptr());
-    let htif1 = dma.isr.read().htif1().bit();
-    let tcif1 = dma.isr.read().tcif1().bit();
-    // set the global clear bit of DMA channel1
-    dma.ifcr.write(|w| w.cgif1().set_bit());
-
-    dma_ch.ch2.disable();
-    dma_ch.ch2.set_transfer_length(BUFFER_SIZE as u16);
-    if htif1 == true {
-        dma_ch.ch2.set_memory_address(tx_dma_buf_first_addr, true);
-        dma_ch.ch2.enable();
-        // hprintln!("DMA_CHANNEL1 half transfer compleated {:?} {:?}", htif1, tx_dma_buf_first_addr).unwrap();
-    } else if tcif1 == true {
-        dma_ch.ch2.set_memory_address(tx_dma_buf_second_addr, true);
-        dma_ch.ch2.enable();
-        // hprintln!("DMA_CHANNEL1 transfer compleated {:?}  {:?}", tcif1, tx_dma_buf_second_addr).unwrap();
-    }
+    // Send the half which has just been filled by the DMA, while it fills the other one.
+    // The bytes are written into the usart fifo, so that this is done before the next half is ready.
+    let _ = adc_buffer.read(|samples, _half| {
+        for sample in samples {
+            for byte in sample.to_le_bytes() {
+                block!(tx.write(byte)).ok();
+            }
+        }
+    });
 }
 
 #[entry]
@@ -102,59 +94,6 @@ fn main() -> ! {
         )
         .unwrap();
 
-    // DMA example
-    //==================================================
-    let adc_buffer1: [u16; BUFFER_SIZE as usize] = [0; BUFFER_SIZE as usize];
-
-    let mut dma = dp.DMA.split(&mut rcc, dp.DMAMUX);
-
-    let adc_ptr = unsafe { &(*hal::stm32::ADC::ptr()) };
-    let adc_data_register_addr = &adc_ptr.dr as *const _ as u32;
-
-    let adc_buffer1_addr: u32 = adc_buffer1.as_ptr() as u32;
-
-    dma.ch1.set_word_size(dma::WordSize::BITS16);
-    dma.ch1.set_direction(dma::Direction::FromPeripheral);
-    dma.ch1.set_memory_address(adc_buffer1_addr, true);
-    dma.ch1
-        .set_peripheral_address(adc_data_register_addr, false);
-    dma.ch1.set_transfer_length(adc_buffer1.len() as u16);
-
-    hprintln!("adc_data_register_addr {:?}", adc_buffer1_addr).unwrap(); // will output addr in dec
-                                                                         // in gdb read the data bytes with:  x /32xh 0x???????   (last is addr in hex)
-                                                                         // or put addr in dec format:   x /32xh 536878092
-                                                                         // https://sourceware.org/gdb/current/onlinedocs/gdb/Memory.html
-
-    // dma ch1 reads from ADC register into memory
-    dma.ch1.select_peripheral(hal::dmamux::DmaMuxIndex::ADC);
-    // The dma continuesly fills the buffer, when its full, it starts over again
-    dma.ch1.set_circular_mode(true);
-
-    // Enabel dma irq for half and full buffer, when reached, so that the second dma ch2 can be started
-    dma.ch1.listen(hal::dma::Event::HalfTransfer);
-    dma.ch1.listen(hal::dma::Event::TransferComplete);
-
-    let (mut tx, mut _rx) = usart1.split();
-
-    let usart = unsafe { &(*stm32::USART1::ptr()) };
-    let tx_data_register_addr = &usart.tdr as *const _ as u32;
-
-    dma.ch2.set_direction(dma::Direction::FromMemory);
-    dma.ch2.set_peripheral_address(tx_data_register_addr, false);
-    dma.ch2.set_word_size(hal::dma::WordSize::BITS8);
-
-    dma.ch2.select_peripheral(tx.dmamux());
-
-    tx.enable_dma();
-
-    // start dma transfer
-    dma.ch1.enable();
-
-    cortex_m::interrupt::free(|cs| *G_DMA.borrow(cs).borrow_mut() = Some(dma));
-    cortex_m::interrupt::free(|cs| {
-        *G_DMA_BUFFER_ADDR.borrow(cs).borrow_mut() = Some(adc_buffer1_addr)
-    });
-
     //==================================================
     // Set up adc
 
@@ -185,10 +124,26 @@ fn main() -> ! {
         tim.cr2.modify(|_, w| w.mms().bits(3 as u8));
     }
 
-    // enable dma to be called, when adc is ready to read
-    adc.dma_enable(true);
+    // let the adc request the dma after each conversion, in circular mode
     adc.dma_circualr_mode(true);
 
+    let (tx, _rx) = usart1.split();
+    cortex_m::interrupt::free(|cs| *G_TX.borrow(cs).borrow_mut() = Some(tx));
+
+    // DMA example
+    //==================================================
+    let dma = dp.DMA.split(&mut rcc, dp.DMAMUX);
+
+    // dma ch1 reads from ADC register into memory.
+    // The dma continuesly fills the buffer, when its full, it starts over again
+    let adc_buffer = cortex_m::singleton!(: [[u16; HALF_SIZE]; 2] = [[0; HALF_SIZE]; 2]).unwrap();
+    let mut adc_buffer = CircBuffer::new(dma.ch1, adc, adc_buffer);
+
+    // Enabel dma irq for half and full buffer, so that the filled half can be sent
+    adc_buffer.listen();
+
+    cortex_m::interrupt::free(|cs| *G_ADC_BUFFER.borrow(cs).borrow_mut() = Some(adc_buffer));
+
     // don't enabel the timer bevor the dma
     // Set up a timer expiring after
     timer.start(50.micros());
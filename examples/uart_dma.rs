@@ -7,7 +7,7 @@ extern crate stm32g0xx_hal as hal;
 
 use core::fmt::Write;
 
-use hal::dma::{self, Channel, Event, Target};
+use hal::dma::Transfer;
 use hal::prelude::*;
 use hal::serial::*;
 use hal::stm32;
@@ -33,56 +33,36 @@ fn main() -> ! {
 
     writeln!(usart1, "Hello without DMA\r\n").unwrap();
 
-    let tx_buffer: [u8; 16] = *b"Hello with DMA!\n";
+    let (tx, _rx) = usart1.split();
 
-    let (mut tx, _rx) = usart1.split();
+    let dma = dp.DMA.split(&mut rcc, dp.DMAMUX);
 
-    let mut dma = dp.DMA.split(&mut rcc, dp.DMAMUX);
+    let tx_buffer = cortex_m::singleton!(: [u8; 16] = *b"Hello with DMA!\n").unwrap();
 
-    // Setup DMA for USART1 TX with dma channel 1.
-    let usart = unsafe { &(*stm32::USART1::ptr()) };
-    let tx_data_register_addr = &usart.tdr as *const _ as u32;
-    let tx_dma_buf_addr: u32 = tx_buffer.as_ptr() as u32;
+    // Transfer the buffer to USART1 TX with dma channel 1.
+    let transfer = Transfer::write(dma.ch1, tx, tx_buffer);
 
-    dma.ch1.set_direction(dma::Direction::FromMemory);
-    dma.ch1.set_memory_address(tx_dma_buf_addr, true);
-    dma.ch1.set_peripheral_address(tx_data_register_addr, false);
-    dma.ch1.set_transfer_length(tx_buffer.len() as u16);
+    // The channel, the usart and the buffer are handed back when the transfer is complete
+    let (mut ch1, mut tx, _) = transfer.wait();
 
-    // Configure dmamux for dma ch1 and usart tx
-    dma.ch1.select_peripheral(tx.dmamux());
-
-    dma.ch1.listen(Event::TransferComplete);
-
-    tx.enable_dma();
-    dma.ch1.enable();
-
-    // Create a second buffer to send after the first dma transfer has completed
-    let mut tx_buffer2: [u8; 23] = *b"Transfer complete {0}!\n";
-    let tx_dma_buf_addr: u32 = tx_buffer2.as_ptr() as u32;
+    // Create a second buffer to send repeatedly
+    let mut tx_buffer2 = cortex_m::singleton!(: [u8; 23] = *b"Transfer complete {0}!\n").unwrap();
 
     let mut delay = dp.TIM1.delay(&mut rcc);
 
     loop {
-        if dma.ch1.event_occurred(Event::TransferComplete) {
-            dma.ch1.clear_event(Event::TransferComplete);
-
-            // update the char between '{ }' in tx_buffer2
-            tx_buffer2[19] += 1;
+        // update the char between '{ }' in tx_buffer2
+        tx_buffer2[19] += 1;
 
-            // wrap around to ascii value 33 == '!', so that we only use printable characters.
-            if tx_buffer2[19] > 126 {
-                tx_buffer2[19] = 33;
-            }
-
-            dma.ch1.disable();
+        // wrap around to ascii value 33 == '!', so that we only use printable characters.
+        if tx_buffer2[19] > 126 {
+            tx_buffer2[19] = 33;
+        }
 
-            led.toggle().unwrap();
+        let transfer = Transfer::write(ch1, tx, tx_buffer2);
+        (ch1, tx, tx_buffer2) = transfer.wait();
 
-            dma.ch1.set_memory_address(tx_dma_buf_addr, true);
-            dma.ch1.set_transfer_length(tx_buffer2.len() as u16);
-            dma.ch1.enable();
-        }
+        led.toggle().unwrap();
 
         delay.delay(500.millis());
     }
@@ -0,0 +1,61 @@
+// #![deny(warnings)]
+#![deny(unsafe_code)]
+#![no_main]
+#![no_std]
+
+extern crate cortex_m_rt as rt;
+extern crate panic_halt;
+extern crate stm32g0xx_hal as hal;
+
+use core::fmt::Write;
+
+use hal::dma;
+use hal::prelude::*;
+use hal::serial::*;
+use hal::stm32;
+use rt::entry;
+
+#[entry]
+fn main() -> ! {
+    let dp = stm32::Peripherals::take().expect("cannot take peripherals");
+    let mut rcc = dp.RCC.constrain();
+    let gpioa = dp.GPIOA.split(&mut rcc);
+
+    let usart1 = dp
+        .USART1
+        .usart(
+            (gpioa.pa9, gpioa.pa10),
+            FullConfig::default()
+                .baudrate(115200.bps())
+                .receiver_timeout_us(1000),
+            &mut rcc,
+        )
+        .unwrap();
+
+    let (mut tx1, rx1) = usart1.split();
+
+    let dma = dp.DMA.split(&mut rcc, dp.DMAMUX);
+
+    // Receive into the ring buffer in the background
+    let buffer = cortex_m::singleton!(: [u8; 64] = [0; 64]).unwrap();
+    let mut ring = rx1.into_ring_buffer(dma.ch1, buffer);
+
+    let mut message = [0u8; 64];
+    loop {
+        // Echo every message once the line has been quiet for a millisecond
+        if ring.target().timeout_lapsed() {
+            ring.target().clear_timeout();
+
+            match ring.read(&mut message) {
+                Ok(len) => {
+                    for byte in &message[..len] {
+                        nb::block!(tx1.write(*byte)).unwrap();
+                    }
+                }
+                Err(dma::Error::Overrun) => {
+                    writeln!(tx1, "Overrun\r").unwrap();
+                }
+            }
+        }
+    }
+}
@@ -1,6 +1,8 @@
 //! # Analog to Digital converter
 use core::ptr;
 
+use crate::dma;
+use crate::dmamux::DmaMuxIndex;
 use crate::gpio::*;
 use crate::rcc::{Enable, Rcc};
 use crate::stm32::ADC;
@@ -354,6 +356,28 @@ impl DmaMode<Adc> for Adc {
     }
 }
 
+impl dma::Target for Adc {
+    fn dmamux(&self) -> DmaMuxIndex {
+        DmaMuxIndex::ADC
+    }
+
+    fn enable_dma(&mut self) {
+        self.rb.cfgr1.modify(|_, w| w.dmaen().set_bit());
+    }
+
+    fn disable_dma(&mut self) {
+        self.rb.cfgr1.modify(|_, w| w.dmaen().clear_bit());
+    }
+}
+
+unsafe impl dma::TargetAddress<dma::PeripheralToMemory> for Adc {
+    type MemSize = u16;
+
+    fn address(&self) -> u32 {
+        &self.rb.dr as *const _ as u32
+    }
+}
+
 impl<WORD, PIN> OneShot<Adc, WORD, PIN> for Adc
 where
     WORD: From<u16>,
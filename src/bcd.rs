@@ -0,0 +1,36 @@
+//! Binary coded decimal conversions of the RTC calendar fields
+
+/// Split `value` (0-99) into its tens and units digits
+///
+/// # Panics
+///
+/// Panics if `value` has more than two digits.
+pub fn encode(value: u32) -> (u8, u8) {
+    assert!(value < 100);
+    ((value / 10) as u8, (value % 10) as u8)
+}
+
+/// Combine the tens and units digits into a value
+pub fn decode(tens: u8, units: u8) -> u32 {
+    tens as u32 * 10 + units as u32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        for value in 0..=99 {
+            let (tens, units) = encode(value);
+            assert!(tens < 10 && units < 10);
+            assert_eq!(decode(tens, units), value);
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn three_digits() {
+        encode(100);
+    }
+}
@@ -1,8 +1,14 @@
 //! Direct Memory Access Engine
+use core::mem::ManuallyDrop;
+use core::ptr;
+use core::sync::atomic::{compiler_fence, Ordering};
+
 use crate::dmamux::DmaMuxIndex;
 use crate::rcc::Rcc;
 use crate::stm32::DMAMUX;
 
+pub use embedded_dma::{ReadBuffer, WriteBuffer};
+
 /// Extension trait to split a DMA peripheral into independent channels
 pub trait DmaExt {
     /// The type to split the DMA into
@@ -73,7 +79,29 @@ impl From<WordSize> for u8 {
     }
 }
 
+/// Data types which can be moved by the DMA in a single transaction
+pub trait Word: Copy + crate::Sealed {
+    /// Size of the data type
+    const SIZE: WordSize;
+}
+
+impl crate::Sealed for u8 {}
+impl Word for u8 {
+    const SIZE: WordSize = WordSize::BITS8;
+}
+
+impl crate::Sealed for u16 {}
+impl Word for u16 {
+    const SIZE: WordSize = WordSize::BITS16;
+}
+
+impl crate::Sealed for u32 {}
+impl Word for u32 {
+    const SIZE: WordSize = WordSize::BITS32;
+}
+
 /// DMA events
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub enum Event {
     /// First half of a transfer is done
     HalfTransfer,
@@ -85,6 +113,23 @@ pub enum Event {
     Any,
 }
 
+impl Event {
+    /// Flag bits of this event for channel 1 in the ISR and IFCR registers.
+    ///
+    /// The flags of the other channels are shifted by 4 bits per channel.
+    /// Field names of these registers are not consistent across the PACs
+    /// (https://github.com/stm32-rs/stm32-rs/pull/695), so the flags are
+    /// addressed by their bit offset instead.
+    fn mask(&self) -> u32 {
+        match self {
+            Event::Any => 0b0001,
+            Event::TransferComplete => 0b0010,
+            Event::HalfTransfer => 0b0100,
+            Event::TransferError => 0b1000,
+        }
+    }
+}
+
 mod private {
     use crate::stm32;
 
@@ -165,6 +210,11 @@ pub trait Channel: private::Channel {
         self.ch().ndtr.write(|w| unsafe { w.ndt().bits(len) });
     }
 
+    /// Get the number of words left to transfer.
+    fn get_transfer_remaining(&self) -> u16 {
+        self.ch().ndtr.read().ndt().bits()
+    }
+
     /// Set the word size.
     fn set_word_size(&mut self, wsize: WordSize) {
         self.ch().cr.modify(|_, w| unsafe {
@@ -190,6 +240,14 @@ pub trait Channel: private::Channel {
         self.ch().cr.modify(|_, w| w.circ().bit(circular));
     }
 
+    /// Set the memory-to-memory mode of this channel
+    ///
+    /// In this mode the channel runs without waiting for DMA requests and
+    /// the peripheral address is used as a second memory address.
+    fn set_mem2mem(&mut self, mem2mem: bool) {
+        self.ch().cr.modify(|_, w| w.mem2mem().bit(mem2mem));
+    }
+
     /// Enable the interrupt for the given event
     fn listen(&mut self, event: Event) {
         use Event::*;
@@ -237,15 +295,12 @@ pub trait Channel: private::Channel {
     }
 }
 
-// TODO: Blocked by https://github.com/stm32-rs/stm32-rs/pull/695
-#[cfg(any(feature = "stm32g030", feature = "stm32g031", feature = "stm32g041"))]
 macro_rules! dma {
     (
         channels: {
             $( $Ci:ident: (
                 $chi:ident,
-                $htifi:ident, $tcifi:ident, $teifi:ident, $gifi:ident,
-                $chtifi:ident, $ctcifi:ident, $cteifi:ident, $cgifi:ident,
+                $flags:expr,
                 $MuxCi: ident
             ), )+
         },
@@ -259,6 +314,8 @@ macro_rules! dma {
         /// DMA channels
         pub struct Channels {
             $( pub $chi: $Ci, )+
+            /// DMAMUX request generators
+            pub generators: dmamux::RequestGenerators,
         }
 
         impl Channels {
@@ -296,29 +353,15 @@ macro_rules! dma {
                 }
 
                 fn event_occurred(&self, event: Event) -> bool {
-                    use Event::*;
-
                     // NOTE(unsafe) atomic read
-                    let flags = unsafe { (*DMA::ptr()).isr.read() };
-                    match event {
-                        HalfTransfer => flags.$htifi().bit_is_set(),
-                        TransferComplete => flags.$tcifi().bit_is_set(),
-                        TransferError => flags.$teifi().bit_is_set(),
-                        Any => flags.$gifi().bit_is_set(),
-                    }
+                    let flags = unsafe { (*DMA::ptr()).isr.read().bits() };
+                    flags & (event.mask() << $flags) != 0
                 }
 
                 fn clear_event(&mut self, event: Event) {
-                    use Event::*;
-
                     // NOTE(unsafe) atomic write to a stateless register
                     unsafe {
-                        let _ = &(*DMA::ptr()).ifcr.write(|w| match event {
-                            HalfTransfer => w.$chtifi().set_bit(),
-                            TransferComplete => w.$ctcifi().set_bit(),
-                            TransferError => w.$cteifi().set_bit(),
-                            Any => w.$cgifi().set_bit(),
-                        });
+                        (*DMA::ptr()).ifcr.write(|w| w.bits(event.mask() << $flags));
                     }
                 }
 
@@ -327,32 +370,30 @@ macro_rules! dma {
     }
 }
 
-// TODO: Blocked by https://github.com/stm32-rs/stm32-rs/pull/695
-// #[cfg(any(feature = "stm32g070", feature = "stm32g071", feature = "stm32g081"))]
-// dma!(
-//     channels: {
-//         C1: (ch1, htif1, tcif1, teif1, gif1, chtif1, ctcif1, cteif1, cgif1, C0),
-//         C2: (ch2, htif2, tcif2, teif2, gif2, chtif2, ctcif2, cteif2, cgif2, C1),
-//         C3: (ch3, htif3, tcif3, teif3, gif3, chtif3, ctcif3, cteif3, cgif3, C2),
-//         C4: (ch4, htif4, tcif4, teif4, gif4, chtif4, ctcif4, cteif4, cgif4, C3),
-//         C5: (ch5, htif5, tcif5, teif5, gif5, chtif5, ctcif5, cteif5, cgif5, C4),
-//         C6: (ch6, htif6, tcif6, teif6, gif6, chtif6, ctcif6, cteif6, cgif6, C5),
-//         C7: (ch7, htif7, tcif7, teif7, gif7, chtif7, ctcif7, cteif7, cgif7, C6),
-//     },
-// );
-
 #[cfg(any(feature = "stm32g030", feature = "stm32g031", feature = "stm32g041"))]
 dma!(
     channels: {
-        C1: (ch1, htif1, tcif1, teif1, gif1, chtif1, ctcif1, cteif1, cgif1, C0),
-        C2: (ch2, htif2, tcif2, teif2, gif2, chtif2, ctcif2, cteif2, cgif2, C1),
-        C3: (ch3, htif3, tcif3, teif3, gif3, chtif3, ctcif3, cteif3, cgif3, C2),
-        C4: (ch4, htif4, tcif4, teif4, gif4, chtif4, ctcif4, cteif4, cgif4, C3),
-        C5: (ch5, htif5, tcif5, teif5, gif5, chtif5, ctcif5, cteif5, cgif5, C4),
+        C1: (ch1, 0, C0),
+        C2: (ch2, 4, C1),
+        C3: (ch3, 8, C2),
+        C4: (ch4, 12, C3),
+        C5: (ch5, 16, C4),
+    },
+);
+
+#[cfg(any(feature = "stm32g070", feature = "stm32g071", feature = "stm32g081"))]
+dma!(
+    channels: {
+        C1: (ch1, 0, C0),
+        C2: (ch2, 4, C1),
+        C3: (ch3, 8, C2),
+        C4: (ch4, 12, C3),
+        C5: (ch5, 16, C4),
+        C6: (ch6, 20, C5),
+        C7: (ch7, 24, C6),
     },
 );
 
-#[cfg(any(feature = "stm32g030", feature = "stm32g031", feature = "stm32g041"))]
 impl DmaExt for DMA {
     type Channels = Channels;
 
@@ -391,6 +432,7 @@ impl DmaExt for DMA {
             ch7: C7 {
                 mux: muxchannels.ch6,
             },
+            generators: muxchannels.generators,
         };
         channels.reset();
         channels
@@ -407,3 +449,653 @@ pub trait Target {
     /// Disable DMA on the target
     fn disable_dma(&mut self) {}
 }
+
+/// Transfer direction marker: from memory to the peripheral
+pub struct MemoryToPeripheral;
+
+/// Transfer direction marker: from the peripheral to memory
+pub struct PeripheralToMemory;
+
+/// Trait implemented by DMA targets which have a data register
+/// the DMA can read from or write to in the given direction.
+///
+/// # Safety
+///
+/// `address` must return the address of a data register of the target
+/// which is accessed with the `MemSize` word size.
+pub unsafe trait TargetAddress<DIR>: Target {
+    /// Word size of the data register
+    type MemSize: Word;
+
+    /// Address of the data register
+    fn address(&self) -> u32;
+}
+
+/// Reset the channel and configure it for a transfer between the target and
+/// `len` words of memory at `address`
+pub(crate) fn configure<CH, PERIPH, DIR>(
+    channel: &mut CH,
+    target: &PERIPH,
+    direction: Direction,
+    address: u32,
+    len: usize,
+) where
+    CH: Channel,
+    PERIPH: TargetAddress<DIR>,
+{
+    assert!(len <= u16::MAX as usize);
+
+    channel.reset();
+    channel.select_peripheral(target.dmamux());
+    channel.set_peripheral_address(target.address(), false);
+    channel.set_memory_address(address, true);
+    channel.set_transfer_length(len as u16);
+    channel.set_word_size(PERIPH::MemSize::SIZE);
+    channel.set_direction(direction);
+}
+
+pub(crate) fn start<CH: Channel, PERIPH: Target>(channel: &mut CH, target: &mut PERIPH) {
+    target.enable_dma();
+
+    // Preceding reads and writes of the buffer must not be moved after
+    // the DMA is started
+    compiler_fence(Ordering::Release);
+    channel.enable();
+}
+
+pub(crate) fn stop<CH: Channel, PERIPH: Target>(channel: &mut CH, target: &mut PERIPH) {
+    channel.disable();
+    target.disable_dma();
+
+    // Subsequent reads and writes of the buffer must not be moved before
+    // the DMA is stopped
+    compiler_fence(Ordering::Acquire);
+}
+
+/// Ownership based DMA transfer
+///
+/// The channel, the target and the buffer are owned by the transfer until
+/// it is finished and released by [`wait`](Transfer::wait) or
+/// [`abort`](Transfer::abort). Dropping the transfer aborts it.
+pub struct Transfer<CH: Channel, PERIPH: Target, BUF> {
+    channel: CH,
+    target: PERIPH,
+    buffer: BUF,
+}
+
+impl<CH, PERIPH, BUF> Transfer<CH, PERIPH, BUF>
+where
+    CH: Channel,
+    PERIPH: TargetAddress<MemoryToPeripheral>,
+    BUF: ReadBuffer<Word = PERIPH::MemSize> + 'static,
+{
+    /// Start a transfer of the whole `buffer` to the `target`
+    ///
+    /// An empty buffer is complete right away.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the buffer is longer than 65535 words.
+    pub fn write(channel: CH, target: PERIPH, buffer: BUF) -> Self {
+        // NOTE(unsafe) the buffer is owned by the transfer until it is released
+        let (address, len) = unsafe { buffer.read_buffer() };
+        let mut transfer = Transfer {
+            channel,
+            target,
+            buffer,
+        };
+        configure(
+            &mut transfer.channel,
+            &transfer.target,
+            Direction::FromMemory,
+            address as u32,
+            len,
+        );
+        start(&mut transfer.channel, &mut transfer.target);
+        transfer
+    }
+}
+
+impl<CH, PERIPH, BUF> Transfer<CH, PERIPH, BUF>
+where
+    CH: Channel,
+    PERIPH: TargetAddress<PeripheralToMemory>,
+    BUF: WriteBuffer<Word = PERIPH::MemSize> + 'static,
+{
+    /// Start a transfer from the `target` filling the whole `buffer`
+    ///
+    /// An empty buffer is complete right away.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the buffer is longer than 65535 words.
+    pub fn read(channel: CH, target: PERIPH, mut buffer: BUF) -> Self {
+        // NOTE(unsafe) the buffer is owned by the transfer until it is released
+        let (address, len) = unsafe { buffer.write_buffer() };
+        let mut transfer = Transfer {
+            channel,
+            target,
+            buffer,
+        };
+        configure(
+            &mut transfer.channel,
+            &transfer.target,
+            Direction::FromPeripheral,
+            address as u32,
+            len,
+        );
+        start(&mut transfer.channel, &mut transfer.target);
+        transfer
+    }
+}
+
+impl<CH: Channel, PERIPH: Target, BUF> Transfer<CH, PERIPH, BUF> {
+    fn stop(&mut self) {
+        stop(&mut self.channel, &mut self.target);
+    }
+
+    fn release(self) -> (CH, PERIPH, BUF) {
+        let this = ManuallyDrop::new(self);
+        // NOTE(unsafe) `this` is never used or dropped again
+        unsafe {
+            (
+                ptr::read(&this.channel),
+                ptr::read(&this.target),
+                ptr::read(&this.buffer),
+            )
+        }
+    }
+
+    /// Is the transfer finished?
+    pub fn is_complete(&self) -> bool {
+        // An empty transfer never raises the transfer complete flag
+        self.channel.event_occurred(Event::TransferComplete) || self.remaining() == 0
+    }
+
+    /// Has the transfer been stopped by a transfer error?
+    pub fn is_error(&self) -> bool {
+        self.channel.event_occurred(Event::TransferError)
+    }
+
+    /// Number of words which have not been transferred yet
+    pub fn remaining(&self) -> u16 {
+        self.channel.get_transfer_remaining()
+    }
+
+    /// Block until the transfer is finished or stopped by an error and
+    /// return the channel, the target and the buffer.
+    ///
+    /// The event flags are left untouched, so the outcome can be checked
+    /// afterwards with `Channel::event_occurred`.
+    pub fn wait(mut self) -> (CH, PERIPH, BUF) {
+        while !self.is_complete() && !self.is_error() {}
+        self.stop();
+        self.release()
+    }
+
+    /// Stop the transfer immediately and return the channel, the target
+    /// and the buffer.
+    pub fn abort(mut self) -> (CH, PERIPH, BUF) {
+        self.stop();
+        self.release()
+    }
+}
+
+impl<CH: Channel, PERIPH: Target, BUF> Drop for Transfer<CH, PERIPH, BUF> {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+/// Half of a [`CircBuffer`]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Half {
+    /// First half of the buffer
+    First,
+    /// Second half of the buffer
+    Second,
+}
+
+/// Circular buffer error
+#[derive(Debug, Eq, PartialEq)]
+pub enum Error {
+    /// The DMA has overwritten data which was not read yet
+    Overrun,
+}
+
+/// Continuous circular DMA reception into a double buffer
+///
+/// The DMA fills both halves of the buffer in turn. While it is writing one
+/// half, the other one can be read with [`read`](CircBuffer::read).
+pub struct CircBuffer<CH: Channel, PERIPH: Target, W: 'static, const N: usize> {
+    channel: CH,
+    target: PERIPH,
+    buffer: &'static mut [[W; N]; 2],
+    next: Half,
+}
+
+impl<CH, PERIPH, W, const N: usize> CircBuffer<CH, PERIPH, W, N>
+where
+    CH: Channel,
+    PERIPH: TargetAddress<PeripheralToMemory, MemSize = W>,
+    W: Word,
+{
+    /// Start filling the `buffer` from the `target` in circular mode
+    ///
+    /// # Panics
+    ///
+    /// Panics if the buffer is longer than 65535 words.
+    pub fn new(mut channel: CH, mut target: PERIPH, buffer: &'static mut [[W; N]; 2]) -> Self {
+        configure(
+            &mut channel,
+            &target,
+            Direction::FromPeripheral,
+            buffer.as_ptr() as u32,
+            2 * N,
+        );
+        channel.set_circular_mode(true);
+        start(&mut channel, &mut target);
+
+        CircBuffer {
+            channel,
+            target,
+            buffer,
+            next: Half::First,
+        }
+    }
+
+    /// Call `f` with the next half of the buffer filled by the DMA
+    ///
+    /// Returns `WouldBlock` while the DMA is still filling it, and
+    /// `Error::Overrun` if the DMA has started to overwrite the half before
+    /// or while it was read. After an overrun, reading resumes with the next
+    /// half completed by the DMA.
+    pub fn read<R>(&mut self, f: impl FnOnce(&[W], Half) -> R) -> nb::Result<R, Error> {
+        let (ready, other) = match self.next {
+            Half::First => (Event::HalfTransfer, Event::TransferComplete),
+            Half::Second => (Event::TransferComplete, Event::HalfTransfer),
+        };
+
+        let first_done = self.channel.event_occurred(Event::HalfTransfer);
+        let second_done = self.channel.event_occurred(Event::TransferComplete);
+        if first_done && second_done {
+            // Drop both halves and wait for the next one to be filled again
+            self.channel.clear_event(Event::HalfTransfer);
+            self.channel.clear_event(Event::TransferComplete);
+            return Err(nb::Error::Other(Error::Overrun));
+        }
+        if !self.channel.event_occurred(ready) {
+            return Err(nb::Error::WouldBlock);
+        }
+        self.channel.clear_event(ready);
+
+        // The half must not be read before the flag check above
+        compiler_fence(Ordering::Acquire);
+
+        let half = self.next;
+        let buffer = match half {
+            Half::First => &self.buffer[0],
+            Half::Second => &self.buffer[1],
+        };
+        let ret = f(buffer, half);
+
+        // Nor must it be read after the overrun check below
+        compiler_fence(Ordering::Acquire);
+
+        self.next = match half {
+            Half::First => Half::Second,
+            Half::Second => Half::First,
+        };
+
+        // The DMA finished the other half and moved on to this one
+        if self.channel.event_occurred(other) {
+            Err(nb::Error::Other(Error::Overrun))
+        } else {
+            Ok(ret)
+        }
+    }
+
+    /// Next half of the buffer to be read
+    pub fn readable_half(&self) -> Half {
+        self.next
+    }
+
+    /// Enable the half transfer and transfer complete interrupts
+    pub fn listen(&mut self) {
+        self.channel.listen(Event::HalfTransfer);
+        self.channel.listen(Event::TransferComplete);
+    }
+
+    /// Disable the half transfer and transfer complete interrupts
+    pub fn unlisten(&mut self) {
+        self.channel.unlisten(Event::HalfTransfer);
+        self.channel.unlisten(Event::TransferComplete);
+    }
+
+    /// Access the target while the DMA is running
+    pub fn target(&mut self) -> &mut PERIPH {
+        &mut self.target
+    }
+
+    /// Stop the DMA and return the channel, the target and the buffer
+    pub fn stop(mut self) -> (CH, PERIPH, &'static mut [[W; N]; 2]) {
+        stop(&mut self.channel, &mut self.target);
+        self.channel.set_circular_mode(false);
+
+        let this = ManuallyDrop::new(self);
+        // NOTE(unsafe) `this` is never used or dropped again
+        unsafe {
+            (
+                ptr::read(&this.channel),
+                ptr::read(&this.target),
+                ptr::read(&this.buffer),
+            )
+        }
+    }
+}
+
+impl<CH: Channel, PERIPH: Target, W, const N: usize> Drop for CircBuffer<CH, PERIPH, W, N> {
+    fn drop(&mut self) {
+        stop(&mut self.channel, &mut self.target);
+        self.channel.set_circular_mode(false);
+    }
+}
+
+/// Continuous circular DMA reception into a ring buffer
+///
+/// The DMA writes into the buffer without ever stopping, and the position it
+/// has reached is derived from the remaining transfer count of the channel.
+/// [`read`](RingBuffer::read) returns whatever has arrived since the last
+/// call, so this is well suited for streams of unknown length.
+pub struct RingBuffer<CH: Channel, PERIPH: Target, W: 'static, const N: usize> {
+    channel: CH,
+    target: PERIPH,
+    buffer: &'static mut [W; N],
+    read_pos: usize,
+    wrapped: bool,
+}
+
+impl<CH, PERIPH, W, const N: usize> RingBuffer<CH, PERIPH, W, N>
+where
+    CH: Channel,
+    PERIPH: TargetAddress<PeripheralToMemory, MemSize = W>,
+    W: Word,
+{
+    /// Start filling the `buffer` from the `target` in circular mode
+    ///
+    /// # Panics
+    ///
+    /// Panics if the buffer is longer than 65535 words.
+    pub fn new(mut channel: CH, mut target: PERIPH, buffer: &'static mut [W; N]) -> Self {
+        configure(
+            &mut channel,
+            &target,
+            Direction::FromPeripheral,
+            buffer.as_ptr() as u32,
+            N,
+        );
+        channel.set_circular_mode(true);
+        start(&mut channel, &mut target);
+
+        RingBuffer {
+            channel,
+            target,
+            buffer,
+            read_pos: 0,
+            wrapped: false,
+        }
+    }
+
+    /// Position in the buffer the DMA will write to next
+    fn write_pos(&self) -> usize {
+        N - self.channel.get_transfer_remaining() as usize
+    }
+
+    /// Update the position of the DMA and check that it has not overtaken
+    /// the reader
+    fn sync(&mut self) -> Result<usize, Error> {
+        let mut pos = self.write_pos();
+        if self.channel.event_occurred(Event::TransferComplete) {
+            self.channel.clear_event(Event::TransferComplete);
+            // The position read above may precede the wrap around
+            pos = self.write_pos();
+            if self.wrapped {
+                return Err(Error::Overrun);
+            }
+            self.wrapped = true;
+        }
+
+        if self.wrapped && pos > self.read_pos {
+            Err(Error::Overrun)
+        } else {
+            Ok(pos)
+        }
+    }
+
+    /// Number of words which can be read
+    pub fn len(&mut self) -> Result<usize, Error> {
+        let pos = self.sync()?;
+        Ok(if self.wrapped {
+            N - self.read_pos + pos
+        } else {
+            pos - self.read_pos
+        })
+    }
+
+    /// Is there nothing to read?
+    pub fn is_empty(&mut self) -> Result<bool, Error> {
+        self.len().map(|len| len == 0)
+    }
+
+    /// Copy the words received so far into `buf`, up to its length, and
+    /// return their number
+    ///
+    /// Returns `Error::Overrun` if the DMA has overwritten words before they
+    /// were read. The unread words are dropped in this case and reading
+    /// resumes with the words received afterwards.
+    pub fn read(&mut self, buf: &mut [W]) -> Result<usize, Error> {
+        let pos = match self.sync() {
+            Ok(pos) => pos,
+            Err(err) => {
+                self.resync();
+                return Err(err);
+            }
+        };
+
+        // The buffer must not be read before the DMA position
+        compiler_fence(Ordering::Acquire);
+
+        let end = if self.wrapped { N } else { pos };
+        let len = (end - self.read_pos).min(buf.len());
+        buf[..len].copy_from_slice(&self.buffer[self.read_pos..self.read_pos + len]);
+
+        let mut count = len;
+        if self.wrapped && self.read_pos + len == N {
+            let rest = pos.min(buf.len() - len);
+            buf[len..len + rest].copy_from_slice(&self.buffer[..rest]);
+            count += rest;
+        }
+
+        // Nor after the DMA position is checked again
+        compiler_fence(Ordering::Acquire);
+
+        // The DMA might have overwritten the words while they were copied
+        if let Err(err) = self.sync() {
+            self.resync();
+            return Err(err);
+        }
+
+        self.read_pos += count;
+        if self.read_pos >= N {
+            self.read_pos -= N;
+            self.wrapped = false;
+        }
+        Ok(count)
+    }
+
+    /// Drop all unread words
+    fn resync(&mut self) {
+        self.channel.clear_event(Event::TransferComplete);
+        self.read_pos = self.write_pos();
+        self.wrapped = false;
+    }
+
+    /// Access the target while the DMA is running
+    pub fn target(&mut self) -> &mut PERIPH {
+        &mut self.target
+    }
+
+    /// Stop the DMA and return the channel, the target and the buffer
+    pub fn stop(mut self) -> (CH, PERIPH, &'static mut [W; N]) {
+        stop(&mut self.channel, &mut self.target);
+        self.channel.set_circular_mode(false);
+
+        let this = ManuallyDrop::new(self);
+        // NOTE(unsafe) `this` is never used or dropped again
+        unsafe {
+            (
+                ptr::read(&this.channel),
+                ptr::read(&this.target),
+                ptr::read(&this.buffer),
+            )
+        }
+    }
+}
+
+impl<CH: Channel, PERIPH: Target, W, const N: usize> Drop for RingBuffer<CH, PERIPH, W, N> {
+    fn drop(&mut self) {
+        stop(&mut self.channel, &mut self.target);
+        self.channel.set_circular_mode(false);
+    }
+}
+
+/// Memory-to-memory DMA transfer
+///
+/// Created by [`copy`](MemTransfer::copy) or [`fill`](MemTransfer::fill),
+/// the transfer runs without CPU involvement and owns the channel and both
+/// buffers until it is released by [`wait`](MemTransfer::wait) or
+/// [`abort`](MemTransfer::abort). Dropping the transfer aborts it.
+pub struct MemTransfer<CH: Channel, SRC, T: 'static> {
+    channel: CH,
+    src: SRC,
+    dst: &'static mut [T],
+}
+
+impl<CH: Channel, T: Word> MemTransfer<CH, &'static [T], T> {
+    /// Start copying `src` into `dst`
+    ///
+    /// Empty buffers are complete right away.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the buffers have different lengths or are longer than
+    /// 65535 words.
+    pub fn copy(channel: CH, src: &'static [T], dst: &'static mut [T]) -> Self {
+        assert_eq!(src.len(), dst.len());
+        MemTransfer::start(channel, src, src.as_ptr(), true, dst)
+    }
+}
+
+impl<CH: Channel, T: Word> MemTransfer<CH, &'static T, T> {
+    /// Start filling `dst` with `value`
+    ///
+    /// An empty `dst` is complete right away.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `dst` is longer than 65535 words.
+    pub fn fill(channel: CH, value: &'static T, dst: &'static mut [T]) -> Self {
+        MemTransfer::start(channel, value, value as *const T, false, dst)
+    }
+}
+
+impl<CH: Channel, SRC, T: Word> MemTransfer<CH, SRC, T> {
+    fn start(
+        mut channel: CH,
+        src: SRC,
+        src_ptr: *const T,
+        src_inc: bool,
+        dst: &'static mut [T],
+    ) -> Self {
+        assert!(dst.len() <= u16::MAX as usize);
+
+        channel.reset();
+        // Reading from memory, MAR is the source and PAR the destination
+        channel.set_direction(Direction::FromMemory);
+        channel.set_mem2mem(true);
+        channel.set_memory_address(src_ptr as u32, src_inc);
+        channel.set_peripheral_address(dst.as_mut_ptr() as u32, true);
+        channel.set_transfer_length(dst.len() as u16);
+        channel.set_word_size(T::SIZE);
+
+        // Preceding reads and writes of the buffers must not be moved after
+        // the DMA is started
+        compiler_fence(Ordering::Release);
+        channel.enable();
+
+        MemTransfer { channel, src, dst }
+    }
+}
+
+impl<CH: Channel, SRC, T> MemTransfer<CH, SRC, T> {
+    fn stop(&mut self) {
+        self.channel.disable();
+        self.channel.set_mem2mem(false);
+
+        // Subsequent reads and writes of the buffers must not be moved
+        // before the DMA is stopped
+        compiler_fence(Ordering::Acquire);
+    }
+
+    fn release(self) -> (CH, SRC, &'static mut [T]) {
+        let this = ManuallyDrop::new(self);
+        // NOTE(unsafe) `this` is never used or dropped again
+        unsafe {
+            (
+                ptr::read(&this.channel),
+                ptr::read(&this.src),
+                ptr::read(&this.dst),
+            )
+        }
+    }
+
+    /// Is the transfer finished?
+    pub fn is_complete(&self) -> bool {
+        // An empty transfer never raises the transfer complete flag
+        self.channel.event_occurred(Event::TransferComplete) || self.remaining() == 0
+    }
+
+    /// Has the transfer been stopped by a transfer error?
+    pub fn is_error(&self) -> bool {
+        self.channel.event_occurred(Event::TransferError)
+    }
+
+    /// Number of words which have not been transferred yet
+    pub fn remaining(&self) -> u16 {
+        self.channel.get_transfer_remaining()
+    }
+
+    /// Block until the transfer is finished or stopped by an error and
+    /// return the channel, the source and the destination.
+    ///
+    /// The event flags are left untouched, so the outcome can be checked
+    /// afterwards with `Channel::event_occurred`.
+    pub fn wait(mut self) -> (CH, SRC, &'static mut [T]) {
+        while !self.is_complete() && !self.is_error() {}
+        self.stop();
+        self.release()
+    }
+
+    /// Stop the transfer immediately and return the channel, the source
+    /// and the destination.
+    pub fn abort(mut self) -> (CH, SRC, &'static mut [T]) {
+        self.stop();
+        self.release()
+    }
+}
+
+impl<CH: Channel, SRC, T> Drop for MemTransfer<CH, SRC, T> {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
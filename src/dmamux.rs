@@ -1,3 +1,5 @@
+//! DMA request multiplexer
+use crate::gpio::SignalEdge;
 use crate::stm32::DMAMUX;
 
 /// Extension trait to split a DMA peripheral into independent channels
@@ -145,20 +147,97 @@ impl DmaMuxTriggerSync {
     }
 }
 
+fn edge_bits(edge: SignalEdge) -> u8 {
+    match edge {
+        SignalEdge::Rising => 0b01,
+        SignalEdge::Falling => 0b10,
+        SignalEdge::All => 0b11,
+    }
+}
+
+/// Synchronization overrun flags of all channels (CSR)
+#[cfg(any(feature = "stm32g070", feature = "stm32g071", feature = "stm32g081"))]
+fn sync_overrun_flags() -> u32 {
+    // NOTE(unsafe) atomic read
+    unsafe { (*DMAMUX::ptr()).dmamux_csr.read().bits() }
+}
+
+/// Clear synchronization overrun flags (CFR)
+#[cfg(any(feature = "stm32g070", feature = "stm32g071", feature = "stm32g081"))]
+fn clear_sync_overrun_flags(mask: u32) {
+    // NOTE(unsafe) atomic write to a stateless register
+    unsafe { (*DMAMUX::ptr()).dmamux_cfr.write(|w| w.bits(mask)) }
+}
+
+/// Synchronization overrun flags of all channels (CSR)
+#[cfg(any(feature = "stm32g030", feature = "stm32g031", feature = "stm32g041"))]
+fn sync_overrun_flags() -> u32 {
+    // The register is missing in the PAC of these devices, access it at offset 0x80
+    // NOTE(unsafe) atomic read
+    unsafe { core::ptr::read_volatile((DMAMUX::ptr() as *const u8).add(0x80) as *const u32) }
+}
+
+/// Clear synchronization overrun flags (CFR)
+#[cfg(any(feature = "stm32g030", feature = "stm32g031", feature = "stm32g041"))]
+fn clear_sync_overrun_flags(mask: u32) {
+    // The register is missing in the PAC of these devices, access it at offset 0x84
+    // NOTE(unsafe) atomic write to a stateless register
+    unsafe { core::ptr::write_volatile((DMAMUX::ptr() as *const u8).add(0x84) as *mut u32, mask) }
+}
+
 pub trait DmaMuxChannel {
+    /// Route the DMA requests of the peripheral corresponding to index to this channel
     fn select_peripheral(&mut self, index: DmaMuxIndex);
+
+    /// Only forward `requests` DMA requests (1 to 32) after each `edge` of the
+    /// synchronization `input`
+    ///
+    /// # Panics
+    ///
+    /// Panics if `requests` is out of range.
+    fn enable_sync(&mut self, input: DmaMuxTriggerSync, edge: SignalEdge, requests: u8);
+
+    /// Forward DMA requests without synchronization
+    fn disable_sync(&mut self);
+
+    /// Enable the event output `dmamux_evtX` of this channel, pulsed when
+    /// the last synchronized request has been forwarded
+    fn enable_event_generation(&mut self, enable: bool);
+
+    /// Enable the synchronization overrun interrupt
+    fn listen_sync_overrun(&mut self);
+
+    /// Disable the synchronization overrun interrupt
+    fn unlisten_sync_overrun(&mut self);
+
+    /// Has a synchronization event occurred before all requests were forwarded?
+    fn is_sync_overrun(&self) -> bool;
+
+    /// Clear the synchronization overrun flag
+    fn clear_sync_overrun(&mut self);
 }
 
 macro_rules! dma_mux {
     (
         channels: {
-            $( $Ci:ident: ($chi:ident, $cr:ident), )+
+            $( $Ci:ident: ($chi:ident, $cr:ident, $i:expr), )+
+        },
+        generators: {
+            $( $RGi:ident: ($rgi:ident, $rgcr:ident, $gi:expr, $req:ident), )+
         },
+        rgsr: $rgsr:ident,
+        rgcfr: $rgcfr:ident,
     ) => {
 
         /// DMAMUX channels
         pub struct Channels {
             $( pub $chi: $Ci, )+
+            pub generators: RequestGenerators,
+        }
+
+        /// DMAMUX request generators
+        pub struct RequestGenerators {
+            $( pub $rgi: $RGi, )+
         }
 
         $(
@@ -170,40 +249,163 @@ macro_rules! dma_mux {
             impl DmaMuxChannel for $Ci {
                 fn select_peripheral(&mut self, index: DmaMuxIndex) {
                     let reg = unsafe { &(*DMAMUX::ptr()).$cr };
-                    reg.write( |w| unsafe {
+                    reg.modify(|_, w| unsafe {
                         w.dmareq_id().bits(index.val())
                         .ege().set_bit()
                     });
 
                 }
+
+                fn enable_sync(&mut self, input: DmaMuxTriggerSync, edge: SignalEdge, requests: u8) {
+                    assert!((1..=32).contains(&requests));
+
+                    let reg = unsafe { &(*DMAMUX::ptr()).$cr };
+                    // NBREQ can only be written while SE and EGE are cleared
+                    let ege = reg.read().ege().bit();
+                    reg.modify(|_, w| w.se().clear_bit().ege().clear_bit());
+                    reg.modify(|_, w| unsafe {
+                        w.sync_id().bits(input.val())
+                        .spol().bits(edge_bits(edge))
+                        .nbreq().bits(requests - 1)
+                    });
+                    reg.modify(|_, w| w.se().set_bit().ege().bit(ege));
+                }
+
+                fn disable_sync(&mut self) {
+                    let reg = unsafe { &(*DMAMUX::ptr()).$cr };
+                    reg.modify(|_, w| w.se().clear_bit());
+                }
+
+                fn enable_event_generation(&mut self, enable: bool) {
+                    let reg = unsafe { &(*DMAMUX::ptr()).$cr };
+                    reg.modify(|_, w| w.ege().bit(enable));
+                }
+
+                fn listen_sync_overrun(&mut self) {
+                    let reg = unsafe { &(*DMAMUX::ptr()).$cr };
+                    reg.modify(|_, w| w.soie().set_bit());
+                }
+
+                fn unlisten_sync_overrun(&mut self) {
+                    let reg = unsafe { &(*DMAMUX::ptr()).$cr };
+                    reg.modify(|_, w| w.soie().clear_bit());
+                }
+
+                fn is_sync_overrun(&self) -> bool {
+                    sync_overrun_flags() & (1 << $i) != 0
+                }
+
+                fn clear_sync_overrun(&mut self) {
+                    clear_sync_overrun_flags(1 << $i);
+                }
             }
         )+
 
+        $(
+            /// Singleton that represents a DMAMUX request generator
+            pub struct $RGi {
+                _0: (),
+            }
+
+            impl $RGi {
+                /// Generate `requests` DMA requests (1 to 32) on each `edge`
+                /// of the `signal`
+                ///
+                /// # Panics
+                ///
+                /// Panics if `requests` is out of range.
+                pub fn enable(&mut self, signal: DmaMuxTriggerSync, edge: SignalEdge, requests: u8) {
+                    assert!((1..=32).contains(&requests));
+
+                    let reg = unsafe { &(*DMAMUX::ptr()).$rgcr };
+                    // GNBREQ can only be written while GE is cleared
+                    reg.modify(|_, w| w.ge().clear_bit());
+                    reg.modify(|_, w| unsafe {
+                        w.sig_id().bits(signal.val())
+                        .gpol().bits(edge_bits(edge))
+                        .gnbreq().bits(requests - 1)
+                    });
+                    reg.modify(|_, w| w.ge().set_bit());
+                }
+
+                /// Stop generating requests
+                pub fn disable(&mut self) {
+                    let reg = unsafe { &(*DMAMUX::ptr()).$rgcr };
+                    reg.modify(|_, w| w.ge().clear_bit());
+                }
+
+                /// DMAMUX index to select this generator as the request of a DMA channel
+                pub fn dmamux(&self) -> DmaMuxIndex {
+                    DmaMuxIndex::$req
+                }
+
+                /// Enable the trigger overrun interrupt
+                pub fn listen(&mut self) {
+                    let reg = unsafe { &(*DMAMUX::ptr()).$rgcr };
+                    reg.modify(|_, w| w.oie().set_bit());
+                }
+
+                /// Disable the trigger overrun interrupt
+                pub fn unlisten(&mut self) {
+                    let reg = unsafe { &(*DMAMUX::ptr()).$rgcr };
+                    reg.modify(|_, w| w.oie().clear_bit());
+                }
+
+                /// Has a trigger event occurred before all requests were generated?
+                pub fn is_overrun(&self) -> bool {
+                    // NOTE(unsafe) atomic read
+                    let flags = unsafe { (*DMAMUX::ptr()).$rgsr.read().of().bits() };
+                    flags & (1 << $gi) != 0
+                }
+
+                /// Clear the trigger overrun flag
+                pub fn clear_overrun(&mut self) {
+                    // NOTE(unsafe) atomic write to a stateless register
+                    unsafe { (*DMAMUX::ptr()).$rgcfr.write(|w| w.cof().bits(1 << $gi)) };
+                }
+            }
+        )+
     }
 }
 
 #[cfg(any(feature = "stm32g070", feature = "stm32g071", feature = "stm32g081"))]
 dma_mux!(
     channels: {
-        C0: (ch0, dmamux_c0cr),
-        C1: (ch1, dmamux_c1cr),
-        C2: (ch2, dmamux_c2cr),
-        C3: (ch3, dmamux_c3cr),
-        C4: (ch4, dmamux_c4cr),
-        C5: (ch5, dmamux_c5cr),
-        C6: (ch6, dmamux_c6cr),
+        C0: (ch0, dmamux_c0cr, 0),
+        C1: (ch1, dmamux_c1cr, 1),
+        C2: (ch2, dmamux_c2cr, 2),
+        C3: (ch3, dmamux_c3cr, 3),
+        C4: (ch4, dmamux_c4cr, 4),
+        C5: (ch5, dmamux_c5cr, 5),
+        C6: (ch6, dmamux_c6cr, 6),
     },
+    generators: {
+        RG0: (rg0, dmamux_rg0cr, 0, dmamux_req_gen0),
+        RG1: (rg1, dmamux_rg1cr, 1, dmamux_req_gen1),
+        RG2: (rg2, dmamux_rg2cr, 2, dmamux_req_gen2),
+        RG3: (rg3, dmamux_rg3cr, 3, dmamux_req_gen3),
+    },
+    rgsr: dmamux_rgsr,
+    rgcfr: dmamux_rgcfr,
 );
 
 #[cfg(any(feature = "stm32g030", feature = "stm32g031", feature = "stm32g041"))]
 dma_mux!(
     channels: {
-        C0: (ch0, c0cr),
-        C1: (ch1, c1cr),
-        C2: (ch2, c2cr),
-        C3: (ch3, c3cr),
-        C4: (ch4, c4cr),
+        C0: (ch0, c0cr, 0),
+        C1: (ch1, c1cr, 1),
+        C2: (ch2, c2cr, 2),
+        C3: (ch3, c3cr, 3),
+        C4: (ch4, c4cr, 4),
+    },
+    generators: {
+        RG0: (rg0, rg0cr, 0, dmamux_req_gen0),
+        RG1: (rg1, rg1cr, 1, dmamux_req_gen1),
+        RG2: (rg2, rg2cr, 2, dmamux_req_gen2),
+        RG3: (rg3, rg3cr, 3, dmamux_req_gen3),
     },
+    rgsr: rgsr,
+    rgcfr: rgcfr,
 );
 
 impl DmaMuxExt for DMAMUX {
@@ -220,6 +422,12 @@ impl DmaMuxExt for DMAMUX {
             ch5: C5 { _0: () },
             #[cfg(any(feature = "stm32g070", feature = "stm32g071", feature = "stm32g081"))]
             ch6: C6 { _0: () },
+            generators: RequestGenerators {
+                rg0: RG0 { _0: () },
+                rg1: RG1 { _0: () },
+                rg2: RG2 { _0: () },
+                rg3: RG3 { _0: () },
+            },
         }
     }
 }
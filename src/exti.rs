@@ -3,7 +3,7 @@ use crate::gpio::SignalEdge;
 use crate::stm32::EXTI;
 
 /// EXTI trigger event
-#[derive(Eq, PartialEq, PartialOrd, Clone, Copy)]
+#[derive(Debug, Eq, PartialEq, PartialOrd, Clone, Copy)]
 pub enum Event {
     GPIO0 = 0,
     GPIO1 = 1,
@@ -62,6 +62,11 @@ impl Event {
             13 => Event::GPIO13,
             14 => Event::GPIO14,
             15 => Event::GPIO15,
+            16 => Event::PVD,
+            #[cfg(any(feature = "stm32g071", feature = "stm32g081"))]
+            17 => Event::COMP1,
+            #[cfg(any(feature = "stm32g071", feature = "stm32g081"))]
+            18 => Event::COMP2,
             _ => unreachable!(),
         }
     }
@@ -80,6 +85,13 @@ pub trait ExtiExt {
     fn unlisten(&self, ev: Event);
     fn is_pending(&self, ev: Event, edge: SignalEdge) -> bool;
     fn unpend(&self, ev: Event);
+    /// Clear the pending edges of all the configurable lines in `mask`
+    fn unpend_mask(&self, mask: u32);
+    /// Iterate over the pending edges of the configurable lines, in line order
+    fn pending_lines(&self) -> PendingLines;
+    /// Split the EXTI into handles owning one line each, which can be handed
+    /// to independent drivers
+    fn split(self) -> Lines;
 }
 
 impl ExtiExt for EXTI {
@@ -169,7 +181,7 @@ impl ExtiExt for EXTI {
             SignalEdge::Rising => self.rpr1.read().bits() & mask != 0,
             SignalEdge::Falling => self.fpr1.read().bits() & mask != 0,
             SignalEdge::All => {
-                (self.rpr1.read().bits() & mask != 0) && (self.fpr1.read().bits() & mask != 0)
+                (self.rpr1.read().bits() & mask != 0) || (self.fpr1.read().bits() & mask != 0)
             }
         }
     }
@@ -181,4 +193,371 @@ impl ExtiExt for EXTI {
             self.fpr1.modify(|_, w| unsafe { w.bits(1 << line) });
         }
     }
+
+    fn unpend_mask(&self, mask: u32) {
+        let mask = mask & ((1 << (TRIGGER_MAX + 1)) - 1);
+        self.rpr1.write(|w| unsafe { w.bits(mask) });
+        self.fpr1.write(|w| unsafe { w.bits(mask) });
+    }
+
+    fn pending_lines(&self) -> PendingLines {
+        let mask = (1 << (TRIGGER_MAX + 1)) - 1;
+        PendingLines {
+            rising: self.rpr1.read().bits() & mask,
+            falling: self.fpr1.read().bits() & mask,
+        }
+    }
+
+    fn split(self) -> Lines {
+        Lines {
+            gpio0: ExtiLine { _0: () },
+            gpio1: ExtiLine { _0: () },
+            gpio2: ExtiLine { _0: () },
+            gpio3: ExtiLine { _0: () },
+            gpio4: ExtiLine { _0: () },
+            gpio5: ExtiLine { _0: () },
+            gpio6: ExtiLine { _0: () },
+            gpio7: ExtiLine { _0: () },
+            gpio8: ExtiLine { _0: () },
+            gpio9: ExtiLine { _0: () },
+            gpio10: ExtiLine { _0: () },
+            gpio11: ExtiLine { _0: () },
+            gpio12: ExtiLine { _0: () },
+            gpio13: ExtiLine { _0: () },
+            gpio14: ExtiLine { _0: () },
+            gpio15: ExtiLine { _0: () },
+            pvd: ExtiLine { _0: () },
+            #[cfg(any(feature = "stm32g071", feature = "stm32g081"))]
+            comp1: ExtiLine { _0: () },
+            #[cfg(any(feature = "stm32g071", feature = "stm32g081"))]
+            comp2: ExtiLine { _0: () },
+            rtc: ExtiLine { _0: () },
+            tamp: ExtiLine { _0: () },
+            i2c1: ExtiLine { _0: () },
+            usart1: ExtiLine { _0: () },
+            usart2: ExtiLine { _0: () },
+            #[cfg(any(feature = "stm32g071", feature = "stm32g081"))]
+            cec: ExtiLine { _0: () },
+            lpuart1: ExtiLine { _0: () },
+            lptim1: ExtiLine { _0: () },
+            lptim2: ExtiLine { _0: () },
+            lse_css: ExtiLine { _0: () },
+            #[cfg(any(feature = "stm32g071", feature = "stm32g081"))]
+            ucpd1: ExtiLine { _0: () },
+            #[cfg(any(feature = "stm32g071", feature = "stm32g081"))]
+            ucpd2: ExtiLine { _0: () },
+        }
+    }
+}
+
+/// Iterator over the pending edges of the configurable lines
+///
+/// The pending registers are read once when the iterator is created. A line
+/// with both edges pending is yielded twice, rising edge first.
+pub struct PendingLines {
+    rising: u32,
+    falling: u32,
+}
+
+impl Iterator for PendingLines {
+    type Item = (Event, SignalEdge);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let pending = self.rising | self.falling;
+        if pending == 0 {
+            return None;
+        }
+        let line = pending.trailing_zeros() as u8;
+        let mask = 1 << line;
+        let edge = if self.rising & mask != 0 {
+            self.rising &= !mask;
+            SignalEdge::Rising
+        } else {
+            self.falling &= !mask;
+            SignalEdge::Falling
+        };
+        Some((Event::from_code(line), edge))
+    }
+}
+
+/// EXTI lines, each owned by its handle
+pub struct Lines {
+    pub gpio0: ExtiLine<0>,
+    pub gpio1: ExtiLine<1>,
+    pub gpio2: ExtiLine<2>,
+    pub gpio3: ExtiLine<3>,
+    pub gpio4: ExtiLine<4>,
+    pub gpio5: ExtiLine<5>,
+    pub gpio6: ExtiLine<6>,
+    pub gpio7: ExtiLine<7>,
+    pub gpio8: ExtiLine<8>,
+    pub gpio9: ExtiLine<9>,
+    pub gpio10: ExtiLine<10>,
+    pub gpio11: ExtiLine<11>,
+    pub gpio12: ExtiLine<12>,
+    pub gpio13: ExtiLine<13>,
+    pub gpio14: ExtiLine<14>,
+    pub gpio15: ExtiLine<15>,
+    pub pvd: ExtiLine<16>,
+    #[cfg(any(feature = "stm32g071", feature = "stm32g081"))]
+    pub comp1: ExtiLine<17>,
+    #[cfg(any(feature = "stm32g071", feature = "stm32g081"))]
+    pub comp2: ExtiLine<18>,
+    pub rtc: ExtiLine<19>,
+    pub tamp: ExtiLine<21>,
+    pub i2c1: ExtiLine<23>,
+    pub usart1: ExtiLine<25>,
+    pub usart2: ExtiLine<26>,
+    #[cfg(any(feature = "stm32g071", feature = "stm32g081"))]
+    pub cec: ExtiLine<27>,
+    pub lpuart1: ExtiLine<28>,
+    pub lptim1: ExtiLine<29>,
+    pub lptim2: ExtiLine<30>,
+    pub lse_css: ExtiLine<31>,
+    #[cfg(any(feature = "stm32g071", feature = "stm32g081"))]
+    pub ucpd1: ExtiLine<32>,
+    #[cfg(any(feature = "stm32g071", feature = "stm32g081"))]
+    pub ucpd2: ExtiLine<33>,
+}
+
+/// Interrupt mask of the EXTI line `N`, accessed either through the `EXTI`
+/// peripheral or through the line handle obtained with `ExtiExt::split`
+pub trait LineControl<const N: u8> {
+    /// Enable the interrupt of the line
+    fn listen_line(&mut self);
+    /// Disable the interrupt of the line
+    fn unlisten_line(&mut self);
+}
+
+/// Trigger edges of the configurable EXTI line `N`
+pub trait LineTrigger<const N: u8>: LineControl<N> {
+    /// Select the edges of the signal which trigger the line
+    fn set_line_trigger(&mut self, edge: SignalEdge);
+}
+
+impl<const N: u8> LineControl<N> for EXTI {
+    fn listen_line(&mut self) {
+        modify_imr(N, true);
+    }
+
+    fn unlisten_line(&mut self) {
+        modify_imr(N, false);
+    }
+}
+
+impl<const N: u8> LineTrigger<N> for EXTI {
+    fn set_line_trigger(&mut self, edge: SignalEdge) {
+        assert!(N <= TRIGGER_MAX);
+        set_trigger(N, edge);
+    }
+}
+
+impl<const N: u8> LineControl<N> for ExtiLine<N> {
+    fn listen_line(&mut self) {
+        self.listen();
+    }
+
+    fn unlisten_line(&mut self) {
+        self.unlisten();
+    }
+}
+
+/// GPIO pin which can be routed to the EXTI line `N`
+pub trait ExtiPin<const N: u8> {
+    /// Port of the pin as selected in EXTICR
+    const PORT: u8;
+}
+
+/// Singleton that represents the EXTI line `N`
+pub struct ExtiLine<const N: u8> {
+    _0: (),
+}
+
+impl<const N: u8> ExtiLine<N> {
+    /// Number of the line
+    pub fn line(&self) -> u8 {
+        N
+    }
+
+    /// Enable the interrupt of the line
+    pub fn listen(&mut self) {
+        modify_imr(N, true);
+    }
+
+    /// Disable the interrupt of the line
+    pub fn unlisten(&mut self) {
+        modify_imr(N, false);
+    }
+
+    /// Enable the event of the line, which wakes up the core from `WFE`
+    /// without running an interrupt handler
+    pub fn listen_event(&mut self) {
+        modify_emr(N, true);
+    }
+
+    /// Disable the event of the line
+    pub fn unlisten_event(&mut self) {
+        modify_emr(N, false);
+    }
+
+    /// Route the GPIO `pin` to the line
+    pub fn select_pin<PIN: ExtiPin<N>>(&mut self, _pin: &PIN) {
+        let offset = (N % 4) * 8;
+        let mask = (PIN::PORT as u32) << offset;
+        let reset = !(0xff << offset);
+        cortex_m::interrupt::free(|_| {
+            // NOTE(unsafe) read-modify-write of the bits of this line in a
+            // critical section
+            let exti = unsafe { &(*EXTI::ptr()) };
+            match N {
+                0..=3 => exti
+                    .exticr1
+                    .modify(|r, w| unsafe { w.bits(r.bits() & reset | mask) }),
+                4..=7 => exti
+                    .exticr2
+                    .modify(|r, w| unsafe { w.bits(r.bits() & reset | mask) }),
+                8..=11 => exti
+                    .exticr3
+                    .modify(|r, w| unsafe { w.bits(r.bits() & reset | mask) }),
+                12..=15 => exti
+                    .exticr4
+                    .modify(|r, w| unsafe { w.bits(r.bits() & reset | mask) }),
+                _ => unreachable!(),
+            }
+        });
+    }
+}
+
+macro_rules! configurable_lines {
+    ($($n:expr),+) => {
+        $(
+            impl ExtiLine<$n> {
+                /// Select the edges of the signal which trigger the line
+                pub fn set_trigger(&mut self, edge: SignalEdge) {
+                    set_trigger($n, edge);
+                }
+
+                /// Trigger the line from software
+                pub fn trigger(&mut self) {
+                    // NOTE(unsafe) writing 0 to the other bits has no effect
+                    unsafe { (*EXTI::ptr()).swier1.write(|w| w.bits(1 << $n)) };
+                }
+
+                /// Is an `edge` of the signal pending? `SignalEdge::All`
+                /// matches either edge.
+                pub fn is_pending(&self, edge: SignalEdge) -> bool {
+                    // NOTE(unsafe) atomic reads with no side effects
+                    let exti = unsafe { &(*EXTI::ptr()) };
+                    let mask = 1 << $n;
+                    let rising = || exti.rpr1.read().bits() & mask != 0;
+                    let falling = || exti.fpr1.read().bits() & mask != 0;
+                    match edge {
+                        SignalEdge::Rising => rising(),
+                        SignalEdge::Falling => falling(),
+                        SignalEdge::All => rising() || falling(),
+                    }
+                }
+
+                /// Clear the pending edges of the line
+                pub fn unpend(&mut self) {
+                    // NOTE(unsafe) atomic writes to stateless registers
+                    let exti = unsafe { &(*EXTI::ptr()) };
+                    exti.rpr1.write(|w| unsafe { w.bits(1 << $n) });
+                    exti.fpr1.write(|w| unsafe { w.bits(1 << $n) });
+                }
+            }
+
+            impl LineTrigger<$n> for ExtiLine<$n> {
+                fn set_line_trigger(&mut self, edge: SignalEdge) {
+                    self.set_trigger(edge);
+                }
+            }
+        )+
+    };
+}
+
+configurable_lines!(0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15);
+#[cfg(any(
+    feature = "stm32g031",
+    feature = "stm32g041",
+    feature = "stm32g071",
+    feature = "stm32g081"
+))]
+configurable_lines!(16);
+#[cfg(any(feature = "stm32g071", feature = "stm32g081"))]
+configurable_lines!(17, 18);
+
+/// Lowest line from `first` to `last` with a pending edge
+///
+/// This decodes which line triggered a shared interrupt, like `EXTI4_15`.
+pub fn pending_line(first: u8, last: u8) -> Option<Event> {
+    // NOTE(unsafe) atomic reads with no side effects
+    let exti = unsafe { &(*EXTI::ptr()) };
+    let pending = exti.rpr1.read().bits() | exti.fpr1.read().bits();
+    (first..=last.min(TRIGGER_MAX))
+        .find(|line| pending & (1 << line) != 0)
+        .map(Event::from_code)
+}
+
+fn modify_imr(line: u8, set: bool) {
+    cortex_m::interrupt::free(|_| {
+        // NOTE(unsafe) read-modify-write of the bit of this line in a
+        // critical section
+        let exti = unsafe { &(*EXTI::ptr()) };
+        match line {
+            line if line < 32 => exti
+                .imr1
+                .modify(|r, w| unsafe { w.bits(set_bit(r.bits(), line, set)) }),
+            #[cfg(any(feature = "stm32g071", feature = "stm32g081"))]
+            line => exti
+                .imr2
+                .modify(|r, w| unsafe { w.bits(set_bit(r.bits(), line - 32, set)) }),
+            #[cfg(not(any(feature = "stm32g071", feature = "stm32g081")))]
+            _ => unreachable!(),
+        }
+    });
+}
+
+fn set_trigger(line: u8, edge: SignalEdge) {
+    let (rising, falling) = match edge {
+        SignalEdge::Rising => (true, false),
+        SignalEdge::Falling => (false, true),
+        SignalEdge::All => (true, true),
+    };
+    cortex_m::interrupt::free(|_| {
+        // NOTE(unsafe) read-modify-write of the bits of this line in a
+        // critical section
+        let exti = unsafe { &(*EXTI::ptr()) };
+        exti.rtsr1
+            .modify(|r, w| unsafe { w.bits(set_bit(r.bits(), line, rising)) });
+        exti.ftsr1
+            .modify(|r, w| unsafe { w.bits(set_bit(r.bits(), line, falling)) });
+    });
+}
+
+fn modify_emr(line: u8, set: bool) {
+    cortex_m::interrupt::free(|_| {
+        // NOTE(unsafe) read-modify-write of the bit of this line in a
+        // critical section
+        let exti = unsafe { &(*EXTI::ptr()) };
+        match line {
+            line if line < 32 => exti
+                .emr1
+                .modify(|r, w| unsafe { w.bits(set_bit(r.bits(), line, set)) }),
+            #[cfg(any(feature = "stm32g071", feature = "stm32g081"))]
+            line => exti
+                .emr2
+                .modify(|r, w| unsafe { w.bits(set_bit(r.bits(), line - 32, set)) }),
+            #[cfg(not(any(feature = "stm32g071", feature = "stm32g081")))]
+            _ => unreachable!(),
+        }
+    });
+}
+
+fn set_bit(bits: u32, bit: u8, set: bool) -> u32 {
+    if set {
+        bits | (1 << bit)
+    } else {
+        bits & !(1 << bit)
+    }
 }
@@ -18,6 +18,31 @@ pub const NUM_PAGES: u32 = 64;
 const FLASH_KEY1: u32 = 0x4567_0123;
 const FLASH_KEY2: u32 = 0xCDEF_89AB;
 
+#[cfg(feature = "stm32g0x1")]
+const OPT_KEY1: u32 = 0x0819_2A3B;
+#[cfg(feature = "stm32g0x1")]
+const OPT_KEY2: u32 = 0x4C5D_6E7F;
+
+/// Brown-out reset threshold, see the datasheet for the voltage of each
+/// level
+#[cfg(feature = "stm32g0x1")]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BorLevel {
+    Level0 = 0b00,
+    Level1 = 0b01,
+    Level2 = 0b10,
+    Level3 = 0b11,
+}
+
+/// Brown-out reset thresholds, the reset is asserted when VDD drops below
+/// `falling` and released when it rises above `rising`
+#[cfg(feature = "stm32g0x1")]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BrownOutReset {
+    pub falling: BorLevel,
+    pub rising: BorLevel,
+}
+
 impl FlashPage {
     /// This gives the starting address of a flash page in physical address
     pub const fn to_address(&self) -> usize {
@@ -215,6 +240,88 @@ impl WriteErase for UnlockedFlash {
     }
 }
 
+#[cfg(feature = "stm32g0x1")]
+impl UnlockedFlash {
+    /// Brown-out reset configuration loaded from the option bytes, `None`
+    /// when only the power-on/power-down reset is active
+    pub fn brown_out_reset(&self) -> Option<BrownOutReset> {
+        let optr = self.f.optr.read();
+        if optr.boren().bit_is_clear() {
+            return None;
+        }
+        let level = |bits| match bits {
+            0b00 => BorLevel::Level0,
+            0b01 => BorLevel::Level1,
+            0b10 => BorLevel::Level2,
+            _ => BorLevel::Level3,
+        };
+        Some(BrownOutReset {
+            falling: level(optr.borf_lev().bits()),
+            rising: level(optr.borr_lev().bits()),
+        })
+    }
+
+    /// Program the brown-out reset option bytes, `None` disabling it
+    ///
+    /// The new configuration takes effect after the option bytes are
+    /// loaded, at the next power-on reset or with `launch_option_bytes`.
+    /// Returns `Error::Illegal` if the rising level is below the falling one.
+    pub fn set_brown_out_reset(&mut self, bor: Option<BrownOutReset>) -> Result {
+        if let Some(bor) = bor {
+            if (bor.rising as u8) < (bor.falling as u8) {
+                return Err(Error::Illegal);
+            }
+        }
+        self.program_option_bytes(|f| {
+            f.optr.modify(|_, w| match bor {
+                Some(bor) => unsafe {
+                    w.boren()
+                        .set_bit()
+                        .borf_lev()
+                        .bits(bor.falling as u8)
+                        .borr_lev()
+                        .bits(bor.rising as u8)
+                },
+                None => w.boren().clear_bit(),
+            })
+        })
+    }
+
+    /// Load the programmed option bytes, which resets the MCU
+    pub fn launch_option_bytes(self) -> ! {
+        self.f.cr.modify(|_, w| w.obl_launch().set_bit());
+        loop {
+            cortex_m::asm::nop();
+        }
+    }
+
+    fn program_option_bytes<F>(&mut self, closure: F) -> Result
+    where
+        F: FnOnce(&FLASH),
+    {
+        // Wait, while the memory interface is busy.
+        while self.f.sr.read().bsy().bit_is_set() {}
+
+        self.f
+            .optkeyr
+            .write(|w| unsafe { w.optkeyr().bits(OPT_KEY1) });
+        self.f
+            .optkeyr
+            .write(|w| unsafe { w.optkeyr().bits(OPT_KEY2) });
+        if self.f.cr.read().optlock().bit_is_set() {
+            return Err(Error::Illegal);
+        }
+
+        self.clear_errors();
+        closure(&self.f);
+        self.f.cr.modify(|_, w| w.optstrt().set_bit());
+        let result = self.wait();
+
+        self.f.cr.modify(|_, w| w.optlock().set_bit());
+        result
+    }
+}
+
 impl UnlockedFlash {
     fn clear_errors(&mut self) {
         self.f.sr.modify(|_, w| {
@@ -18,11 +18,38 @@ pub trait GpioExt {
     fn split(self, rcc: &mut Rcc) -> Self::Parts;
 }
 
+/// Atomic write of several output pins of one port
+pub trait PortWriter {
+    /// Drive the `k`-th pin of the group high if bit `k` of `value` is set and
+    /// low otherwise, with a single register write
+    fn write(&mut self, value: u16);
+}
+
+/// Atomic read of several pins of one port
+pub trait PortReader {
+    /// Read the levels of the pins of the group with a single register read,
+    /// bit `k` of the result being the level of the `k`-th pin
+    fn read(&self) -> u16;
+}
+
+/// Port and number of a pin, known from its type
+pub trait PinId {
+    /// Port of the pin: 0 for GPIOA, 1 for GPIOB, 2 for GPIOC, 3 for GPIOD
+    /// and 5 for GPIOF
+    const PORT: u8;
+    /// Number of the pin in its port
+    const PIN: u8;
+}
+
 trait GpioRegExt {
     fn is_low(&self, pos: u8) -> bool;
     fn is_set_low(&self, pos: u8) -> bool;
     fn set_high(&self, pos: u8);
     fn set_low(&self, pos: u8);
+    fn set_mode(&self, pos: u8, mode: u32);
+    fn set_open_drain(&self, pos: u8, open_drain: bool);
+    fn set_pull(&self, pos: u8, pull: u32);
+    fn set_alternate(&self, pos: u8, af: u8);
 }
 
 /// Input mode (type state)
@@ -53,9 +80,121 @@ pub struct Output<MODE> {
 /// Push pull output (type state)
 pub struct PushPull;
 
+/// Mode switchable at runtime between inputs and outputs (type state)
+///
+/// Operations which do not fit the current mode fail with [`PinModeError`].
+pub struct Dynamic;
+
+/// Error of an operation not supported by the current mode of a [`Dynamic`] pin
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum PinModeError {
+    IncorrectMode,
+}
+
+/// Alternate function mode `A` with output type `OTYPE` (type state)
+///
+/// `OTYPE` is either [`PushPull`] or [`OpenDrain`].
+pub struct Alternate<const A: u8, OTYPE = PushPull> {
+    _mode: PhantomData<OTYPE>,
+}
+
+/// Pin whose configuration has been frozen until the next reset (type state)
+///
+/// Created by `lock()`, a locked pin can still be read and driven but can not
+/// be reconfigured. Locked alternate function pins can be handed to the
+/// peripheral drivers.
+pub struct Locked<PIN> {
+    pub(crate) pin: PIN,
+}
+
+impl<PIN: OutputPin> OutputPin for Locked<PIN> {
+    type Error = PIN::Error;
+
+    #[inline(always)]
+    fn set_high(&mut self) -> Result<(), Self::Error> {
+        self.pin.set_high()
+    }
+
+    #[inline(always)]
+    fn set_low(&mut self) -> Result<(), Self::Error> {
+        self.pin.set_low()
+    }
+}
+
+impl<PIN: StatefulOutputPin> StatefulOutputPin for Locked<PIN> {
+    #[inline(always)]
+    fn is_set_high(&self) -> Result<bool, Self::Error> {
+        self.pin.is_set_high()
+    }
+
+    #[inline(always)]
+    fn is_set_low(&self) -> Result<bool, Self::Error> {
+        self.pin.is_set_low()
+    }
+}
+
+impl<PIN: StatefulOutputPin> toggleable::Default for Locked<PIN> {}
+
+impl<PIN: PinId> PinId for Locked<PIN> {
+    const PORT: u8 = PIN::PORT;
+    const PIN: u8 = PIN::PIN;
+}
+
+impl<PIN: InputPin> InputPin for Locked<PIN> {
+    type Error = PIN::Error;
+
+    #[inline(always)]
+    fn is_high(&self) -> Result<bool, Self::Error> {
+        self.pin.is_high()
+    }
+
+    #[inline(always)]
+    fn is_low(&self) -> Result<bool, Self::Error> {
+        self.pin.is_low()
+    }
+}
+
+impl<PINS: PortWriter> PortWriter for Locked<PINS> {
+    #[inline(always)]
+    fn write(&mut self, value: u16) {
+        self.pin.write(value)
+    }
+}
+
+impl<PINS: PortReader> PortReader for Locked<PINS> {
+    #[inline(always)]
+    fn read(&self) -> u16 {
+        self.pin.read()
+    }
+}
+
+/// Implement a driver pin trait for a locked pin
+///
+/// The configuration of a locked pin can not be changed, so `$PIN` must be
+/// in the alternate function mode of the peripheral already and `setup`
+/// does nothing.
+macro_rules! locked_pin {
+    (impl<$($gen:ident),*> $Trait:ident<$PERIPH:ty> for $PIN:ty {
+        $(type $Assoc:ident = $Type:ty;)*
+    }) => {
+        impl<$($gen),*> $Trait<$PERIPH> for $crate::gpio::Locked<$PIN> {
+            $(type $Assoc = $Type;)*
+
+            fn setup(&self) {}
+
+            fn release(self) -> Self {
+                self
+            }
+        }
+    };
+}
+
+pub(crate) use locked_pin;
+
 /// Fully erased pin
 pub struct Pin<MODE> {
     i: u8,
+    port_id: u8,
     port: *const dyn GpioRegExt,
     _mode: PhantomData<MODE>,
 }
@@ -82,6 +221,40 @@ macro_rules! gpio_trait {
                 // NOTE(unsafe) atomic write to a stateless register
                 unsafe { self.bsrr.write(|w| w.bits(1 << (pos + 16))) }
             }
+
+            fn set_mode(&self, pos: u8, mode: u32) {
+                let offset = 2 * pos;
+                self.moder.modify(|r, w| unsafe {
+                    w.bits((r.bits() & !(0b11 << offset)) | (mode << offset))
+                });
+            }
+
+            fn set_open_drain(&self, pos: u8, open_drain: bool) {
+                self.otyper.modify(|r, w| unsafe {
+                    w.bits((r.bits() & !(0b1 << pos)) | ((open_drain as u32) << pos))
+                });
+            }
+
+            fn set_pull(&self, pos: u8, pull: u32) {
+                let offset = 2 * pos;
+                self.pupdr.modify(|r, w| unsafe {
+                    w.bits((r.bits() & !(0b11 << offset)) | (pull << offset))
+                });
+            }
+
+            fn set_alternate(&self, pos: u8, af: u8) {
+                let offset = 4 * (pos % 8);
+                let af = af as u32;
+                if pos < 8 {
+                    self.afrl.modify(|r, w| unsafe {
+                        w.bits((r.bits() & !(0b1111 << offset)) | (af << offset))
+                    });
+                } else {
+                    self.afrh.modify(|r, w| unsafe {
+                        w.bits((r.bits() & !(0b1111 << offset)) | (af << offset))
+                    });
+                }
+            }
         }
     };
 }
@@ -95,6 +268,91 @@ unsafe impl<MODE> Sync for Pin<MODE> {}
 // threads
 unsafe impl<MODE> Send for Pin<MODE> {}
 
+impl<MODE> Pin<MODE> {
+    /// Port of the pin: 0 for GPIOA, 1 for GPIOB, 2 for GPIOC, 3 for GPIOD
+    /// and 5 for GPIOF
+    pub fn port(&self) -> u8 {
+        self.port_id
+    }
+
+    /// Number of the pin in its port
+    pub fn pin_id(&self) -> u8 {
+        self.i
+    }
+
+    fn regs(&self) -> &dyn GpioRegExt {
+        // NOTE(unsafe) the pin is the only owner of its bits in the registers
+        unsafe { &*self.port }
+    }
+
+    fn into_mode<NEWMODE>(self, mode: u32, pull: u32) -> Pin<NEWMODE> {
+        self.regs().set_pull(self.i, pull);
+        self.regs().set_mode(self.i, mode);
+        Pin {
+            i: self.i,
+            port_id: self.port_id,
+            port: self.port,
+            _mode: PhantomData,
+        }
+    }
+
+    /// Configures the pin to operate as a floating input pin
+    pub fn into_floating_input(self) -> Pin<Input<Floating>> {
+        self.into_mode(0b00, 0b00)
+    }
+
+    /// Configures the pin to operate as a pulled down input pin
+    pub fn into_pull_down_input(self) -> Pin<Input<PullDown>> {
+        self.into_mode(0b00, 0b10)
+    }
+
+    /// Configures the pin to operate as a pulled up input pin
+    pub fn into_pull_up_input(self) -> Pin<Input<PullUp>> {
+        self.into_mode(0b00, 0b01)
+    }
+
+    /// Configures the pin to operate as an analog pin
+    pub fn into_analog(self) -> Pin<Analog> {
+        self.into_mode(0b11, 0b00)
+    }
+
+    /// Configures the pin to operate as an open drain output pin
+    pub fn into_open_drain_output(self) -> Pin<Output<OpenDrain>> {
+        self.regs().set_open_drain(self.i, true);
+        self.into_mode(0b01, 0b00)
+    }
+
+    /// Configures the pin to operate as a push pull output pin
+    pub fn into_push_pull_output(self) -> Pin<Output<PushPull>> {
+        self.regs().set_open_drain(self.i, false);
+        self.into_mode(0b01, 0b00)
+    }
+
+    /// Configures the pin to operate in the push pull alternate function mode `A`
+    ///
+    /// # Panics
+    ///
+    /// Panics if `A` is not a valid alternate function (0 to 7).
+    pub fn into_alternate<const A: u8>(self) -> Pin<Alternate<A, PushPull>> {
+        assert!(A < 8);
+        self.regs().set_open_drain(self.i, false);
+        self.regs().set_alternate(self.i, A);
+        self.into_mode(0b10, 0b00)
+    }
+
+    /// Configures the pin to operate in the open drain alternate function mode `A`
+    ///
+    /// # Panics
+    ///
+    /// Panics if `A` is not a valid alternate function (0 to 7).
+    pub fn into_alternate_open_drain<const A: u8>(self) -> Pin<Alternate<A, OpenDrain>> {
+        assert!(A < 8);
+        self.regs().set_open_drain(self.i, true);
+        self.regs().set_alternate(self.i, A);
+        self.into_mode(0b10, 0b00)
+    }
+}
+
 impl<MODE> StatefulOutputPin for Pin<Output<MODE>> {
     #[inline(always)]
     fn is_set_high(&self) -> Result<bool, Self::Error> {
@@ -285,6 +543,93 @@ macro_rules! gpio {
                 }
             }
 
+            /// Run the lock key sequence on the pins of the `mask`, and check
+            /// that they are locked
+            ///
+            /// The whole LCKR register is frozen once the key is set, so no
+            /// other pin of the port can be locked afterwards.
+            fn lock_pins(mask: u16) -> bool {
+                let lckk = 1 << 16;
+                let pins = mask as u32;
+                cortex_m::interrupt::free(|_| unsafe {
+                    let gpio = &(*$GPIOX::ptr());
+                    // The lock key sequence must not be interrupted
+                    gpio.lckr.write(|w| w.bits(lckk | pins));
+                    gpio.lckr.write(|w| w.bits(pins));
+                    gpio.lckr.write(|w| w.bits(lckk | pins));
+                    let _ = gpio.lckr.read().bits();
+                    gpio.lckr.read().bits() & (lckk | pins) == lckk | pins
+                })
+            }
+
+            /// Pins of this port which are written or read together
+            pub struct PinGroup<MODE, const N: usize> {
+                pins: [$PXx<MODE>; N],
+            }
+
+            impl<MODE, const N: usize> PinGroup<MODE, N> {
+                /// Group the `pins`, the `k`-th pin being bit `k` of the values
+                /// written and read
+                pub fn new(pins: [$PXx<MODE>; N]) -> Self {
+                    PinGroup { pins }
+                }
+
+                /// Release the pins
+                pub fn release(self) -> [$PXx<MODE>; N] {
+                    self.pins
+                }
+
+                /// Lock the configuration of all the pins of the group until
+                /// the next reset, in a single lock sequence
+                ///
+                /// Fails and returns the group if the port has already been
+                /// locked, which freezes the lock of all its pins.
+                pub fn lock(self) -> Result<Locked<Self>, Self> {
+                    let mask = self.pins.iter().fold(0, |mask, pin| mask | (1 << pin.i));
+                    if lock_pins(mask) {
+                        Ok(Locked { pin: self })
+                    } else {
+                        Err(self)
+                    }
+                }
+
+                fn read_idr(&self) -> u16 {
+                    // NOTE(unsafe) atomic read with no side effects
+                    let idr = unsafe { (*$GPIOX::ptr()).idr.read().bits() };
+                    self.pins
+                        .iter()
+                        .enumerate()
+                        .filter(|(_, pin)| idr & (1 << pin.i) != 0)
+                        .fold(0, |value, (k, _)| value | (1 << k))
+                }
+            }
+
+            impl<MODE, const N: usize> PortWriter for PinGroup<Output<MODE>, N> {
+                fn write(&mut self, value: u16) {
+                    let bsrr = self.pins.iter().enumerate().fold(0, |bsrr, (k, pin)| {
+                        if value & (1 << k) != 0 {
+                            bsrr | (1 << pin.i)
+                        } else {
+                            bsrr | (1 << (pin.i + 16))
+                        }
+                    });
+                    // NOTE(unsafe) atomic write to a stateless register
+                    unsafe { (*$GPIOX::ptr()).bsrr.write(|w| w.bits(bsrr)) };
+                }
+            }
+
+            impl<MODE, const N: usize> PortReader for PinGroup<Output<MODE>, N> {
+                fn read(&self) -> u16 {
+                    self.read_idr()
+                }
+            }
+
+            impl<MODE, const N: usize> PortReader for PinGroup<Input<MODE>, N> {
+                fn read(&self) -> u16 {
+                    self.read_idr()
+                }
+            }
+
             $(
                 pub struct $PXi<MODE> {
                     _mode: PhantomData<MODE>,
@@ -438,6 +783,59 @@ macro_rules! gpio {
                         $PXi { _mode: PhantomData }
                     }
 
+                    /// Configures the pin to operate in the push pull alternate
+                    /// function mode `A`
+                    ///
+                    /// # Panics
+                    ///
+                    /// Panics if `A` is not a valid alternate function (0 to 7).
+                    pub fn into_alternate<const A: u8>(self) -> $PXi<Alternate<A, PushPull>> {
+                        assert!(A < 8);
+                        unsafe {
+                            let gpio = &(*$GPIOX::ptr());
+                            gpio.otyper.modify(|r, w| {
+                                w.bits(r.bits() & !(0b1 << $i))
+                            });
+                        }
+                        self.into_alternate_mode(A)
+                    }
+
+                    /// Configures the pin to operate in the open drain alternate
+                    /// function mode `A`
+                    ///
+                    /// # Panics
+                    ///
+                    /// Panics if `A` is not a valid alternate function (0 to 7).
+                    pub fn into_alternate_open_drain<const A: u8>(self) -> $PXi<Alternate<A, OpenDrain>> {
+                        assert!(A < 8);
+                        unsafe {
+                            let gpio = &(*$GPIOX::ptr());
+                            gpio.otyper.modify(|r, w| {
+                                w.bits(r.bits() | (0b1 << $i))
+                            });
+                        }
+                        self.into_alternate_mode(A)
+                    }
+
+                    fn into_alternate_mode<NEWMODE>(self, af: u8) -> $PXi<NEWMODE> {
+                        let offset = 2 * $i;
+                        unsafe {
+                            let gpio = &(*$GPIOX::ptr());
+                            gpio.pupdr.modify(|r, w| {
+                                w.bits(r.bits() & !(0b11 << offset))
+                            });
+                        }
+                        self.set_alt_mode_bits(af);
+                        $PXi { _mode: PhantomData }
+                    }
+
+                    /// Configures the pin to operate in a mode which can be
+                    /// changed at runtime, starting as a floating input
+                    pub fn into_dynamic(self) -> $PXi<Dynamic> {
+                        let _ = self.into_floating_input();
+                        $PXi { _mode: PhantomData }
+                    }
+
                     /// Configures the pin as external trigger
                     pub fn listen(self, edge: SignalEdge, exti: &mut EXTI) -> $PXi<Input<Floating>> {
                         let offset = 2 * $i;
@@ -471,6 +869,55 @@ macro_rules! gpio {
                         $PXi { _mode: PhantomData }
                     }
 
+                    /// Enable or disable the internal pull up resistor
+                    ///
+                    /// Enabling it replaces the pull down resistor if it was enabled.
+                    pub fn internal_pull_up(self, on: bool) -> Self {
+                        self.set_pull(0b01, on)
+                    }
+
+                    /// Enable or disable the internal pull down resistor
+                    ///
+                    /// Enabling it replaces the pull up resistor if it was enabled.
+                    pub fn internal_pull_down(self, on: bool) -> Self {
+                        self.set_pull(0b10, on)
+                    }
+
+                    fn set_pull(self, pull: u32, on: bool) -> Self {
+                        let offset = 2 * $i;
+                        unsafe {
+                            let gpio = &(*$GPIOX::ptr());
+                            gpio.pupdr.modify(|r, w| {
+                                let current = (r.bits() >> offset) & 0b11;
+                                let bits = if on {
+                                    pull
+                                } else if current == pull {
+                                    0b00
+                                } else {
+                                    current
+                                };
+                                w.bits((r.bits() & !(0b11 << offset)) | (bits << offset))
+                            })
+                        };
+                        self
+                    }
+
+                    /// Lock the configuration of the pin until the next reset
+                    ///
+                    /// The mode, output type, speed, pull and alternate function
+                    /// registers of the pin can not be written anymore.
+                    ///
+                    /// Fails and returns the pin if another pin of the port has
+                    /// already been locked, which freezes the lock of the whole
+                    /// port. Use `PinGroup::lock` to lock several pins of a port.
+                    pub fn lock(self) -> Result<Locked<Self>, Self> {
+                        if lock_pins(1 << $i) {
+                            Ok(Locked { pin: self })
+                        } else {
+                            Err(self)
+                        }
+                    }
+
                     /// Set pin speed
                     pub fn set_speed(self, speed: Speed) -> Self {
                         let offset = 2 * $i;
@@ -484,6 +931,10 @@ macro_rules! gpio {
 
                     #[allow(dead_code)]
                     pub(crate) fn set_alt_mode(&self, mode: AltFunction) {
+                        self.set_alt_mode_bits(mode as u8);
+                    }
+
+                    fn set_alt_mode_bits(&self, mode: u8) {
                         let mode = mode as u32;
                         let offset = 2 * $i;
                         let offset2 = 4 * $i;
@@ -519,16 +970,6 @@ macro_rules! gpio {
                     }
                 }
 
-                impl<MODE> $PXi<Output<MODE>> {
-                    /// Erases the pin number from the type
-                    ///
-                    /// This is useful when you want to collect the pins into an array where you
-                    /// need all the elements to have the same type
-                    pub fn downgrade(self) -> $PXx<Output<MODE>> {
-                        $PXx { i: $i, _mode: self._mode }
-                    }
-                }
-
                 impl<MODE> OutputPin for $PXi<Output<MODE>> {
                     type Error = Infallible;
 
@@ -574,12 +1015,129 @@ macro_rules! gpio {
                     }
                 }
 
-                impl<MODE> $PXi<Input<MODE>> {
+                impl $PXi<Dynamic> {
+                    /// Switch the pin to a floating input
+                    pub fn make_floating_input(&mut self) {
+                        let _ = $PXi::<Dynamic> { _mode: PhantomData }.into_floating_input();
+                    }
+
+                    /// Switch the pin to a pulled up input
+                    pub fn make_pull_up_input(&mut self) {
+                        let _ = $PXi::<Dynamic> { _mode: PhantomData }.into_pull_up_input();
+                    }
+
+                    /// Switch the pin to a pulled down input
+                    pub fn make_pull_down_input(&mut self) {
+                        let _ = $PXi::<Dynamic> { _mode: PhantomData }.into_pull_down_input();
+                    }
+
+                    /// Switch the pin to a push pull output
+                    pub fn make_push_pull_output(&mut self) {
+                        let _ = $PXi::<Dynamic> { _mode: PhantomData }.into_push_pull_output();
+                    }
+
+                    /// Switch the pin to a push pull output in `state`
+                    pub fn make_push_pull_output_in_state(&mut self, state: PinState) {
+                        let _ = $PXi::<Dynamic> { _mode: PhantomData }.into_push_pull_output_in_state(state);
+                    }
+
+                    /// Switch the pin to an open drain output
+                    pub fn make_open_drain_output(&mut self) {
+                        let _ = $PXi::<Dynamic> { _mode: PhantomData }.into_open_drain_output();
+                    }
+
+                    /// Switch the pin to an open drain output in `state`
+                    pub fn make_open_drain_output_in_state(&mut self, state: PinState) {
+                        let _ = $PXi::<Dynamic> { _mode: PhantomData }.into_open_drain_output_in_state(state);
+                    }
+
+                    /// Is the pin currently an output?
+                    pub fn is_output(&self) -> bool {
+                        // NOTE(unsafe) atomic read with no side effects
+                        unsafe { (*$GPIOX::ptr()).moder.read().bits() >> (2 * $i) & 0b11 == 0b01 }
+                    }
+
+                    /// Is the pin currently a push pull output?
+                    fn is_push_pull_output(&self) -> bool {
+                        // NOTE(unsafe) atomic read with no side effects
+                        self.is_output() && unsafe { (*$GPIOX::ptr()).otyper.read().bits() & (1 << $i) == 0 }
+                    }
+                }
+
+                impl OutputPin for $PXi<Dynamic> {
+                    type Error = PinModeError;
+
+                    fn set_high(&mut self) -> Result<(), Self::Error> {
+                        if !self.is_output() {
+                            return Err(PinModeError::IncorrectMode);
+                        }
+                        self.internal_set_state(PinState::High);
+                        Ok(())
+                    }
+
+                    fn set_low(&mut self) -> Result<(), Self::Error> {
+                        if !self.is_output() {
+                            return Err(PinModeError::IncorrectMode);
+                        }
+                        self.internal_set_state(PinState::Low);
+                        Ok(())
+                    }
+                }
+
+                impl StatefulOutputPin for $PXi<Dynamic> {
+                    fn is_set_high(&self) -> Result<bool, Self::Error> {
+                        let is_set_high = !self.is_set_low()?;
+                        Ok(is_set_high)
+                    }
+
+                    fn is_set_low(&self) -> Result<bool, Self::Error> {
+                        if !self.is_output() {
+                            return Err(PinModeError::IncorrectMode);
+                        }
+                        // NOTE(unsafe) atomic read with no side effects
+                        let is_set_low = unsafe { (*$GPIOX::ptr()).odr.read().bits() & (1 << $i) == 0 };
+                        Ok(is_set_low)
+                    }
+                }
+
+                impl toggleable::Default for $PXi<Dynamic> {
+                }
+
+                impl InputPin for $PXi<Dynamic> {
+                    type Error = PinModeError;
+
+                    fn is_high(&self) -> Result<bool, Self::Error> {
+                        let is_high = !self.is_low()?;
+                        Ok(is_high)
+                    }
+
+                    /// Fails if the pin is a push pull output, which only reads
+                    /// back its own output level
+                    fn is_low(&self) -> Result<bool, Self::Error> {
+                        if self.is_push_pull_output() {
+                            return Err(PinModeError::IncorrectMode);
+                        }
+                        // NOTE(unsafe) atomic read with no side effects
+                        let is_low = unsafe { (*$GPIOX::ptr()).idr.read().bits() & (1 << $i) == 0 };
+                        Ok(is_low)
+                    }
+                }
+
+                impl<MODE> crate::exti::ExtiPin<{ $i }> for $PXi<MODE> {
+                    const PORT: u8 = $Pxn;
+                }
+
+                impl<MODE> PinId for $PXi<MODE> {
+                    const PORT: u8 = $Pxn;
+                    const PIN: u8 = $i;
+                }
+
+                impl<MODE> $PXi<MODE> {
                     /// Erases the pin number from the type
                     ///
                     /// This is useful when you want to collect the pins into an array where you
                     /// need all the elements to have the same type
-                    pub fn downgrade(self) -> $PXx<Input<MODE>> {
+                    pub fn downgrade(self) -> $PXx<MODE> {
                         $PXx { i: $i, _mode: self._mode }
                     }
                 }
@@ -606,28 +1164,15 @@ macro_rules! gpio {
                 }
             }
 
-            impl<MODE> $PXx<Output<MODE>> {
-                /// Erases the port number from the type
-                ///
-                /// This is useful when you want to collect the pins into an array where you
-                /// need all the elements to have the same type
-                pub fn downgrade(self) -> Pin<Output<MODE>> {
-                    Pin {
-                        i: self.get_id(),
-                        port: $GPIOX::ptr() as *const dyn GpioRegExt,
-                        _mode: self._mode,
-                    }
-                }
-            }
-
-            impl<MODE> $PXx<Input<MODE>> {
+            impl<MODE> $PXx<MODE> {
                 /// Erases the port number from the type
                 ///
                 /// This is useful when you want to collect the pins into an array where you
                 /// need all the elements to have the same type
-                pub fn downgrade(self) -> Pin<Input<MODE>> {
+                pub fn downgrade(self) -> Pin<MODE> {
                     Pin {
                         i: self.get_id(),
+                        port_id: $Pxn,
                         port: $GPIOX::ptr() as *const dyn GpioRegExt,
                         _mode: self._mode,
                     }
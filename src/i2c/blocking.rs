@@ -108,11 +108,11 @@ macro_rules! busy_wait {
 
 macro_rules! i2c {
     ($I2CX:ident, $i2cx:ident,
-        sda: [ $($PSDA:ty,)+ ],
-        scl: [ $($PSCL:ty,)+ ],
+        sda: [ $($PSDA:ident,)+ ],
+        scl: [ $($PSCL:ident,)+ ],
     ) => {
         $(
-            impl SDAPin<$I2CX> for $PSDA {
+            impl SDAPin<$I2CX> for $PSDA<Output<OpenDrain>> {
                 fn setup(&self) {
                     self.set_alt_mode(AltFunction::AF6)
                 }
@@ -121,10 +121,20 @@ macro_rules! i2c {
                     self.into_open_drain_output()
                 }
             }
+
+            impl SDAPin<$I2CX> for $PSDA<Alternate<{ AltFunction::AF6 as u8 }, OpenDrain>> {
+                fn setup(&self) {}
+
+                fn release(self) -> Self {
+                    self
+                }
+            }
+
+            locked_pin!(impl<> SDAPin<$I2CX> for $PSDA<Alternate<{ AltFunction::AF6 as u8 }, OpenDrain>> {});
         )+
 
         $(
-            impl SCLPin<$I2CX> for $PSCL {
+            impl SCLPin<$I2CX> for $PSCL<Output<OpenDrain>> {
                 fn setup(&self) {
                     self.set_alt_mode(AltFunction::AF6)
                 }
@@ -133,6 +143,16 @@ macro_rules! i2c {
                     self.into_open_drain_output()
                 }
             }
+
+            impl SCLPin<$I2CX> for $PSCL<Alternate<{ AltFunction::AF6 as u8 }, OpenDrain>> {
+                fn setup(&self) {}
+
+                fn release(self) -> Self {
+                    self
+                }
+            }
+
+            locked_pin!(impl<> SCLPin<$I2CX> for $PSCL<Alternate<{ AltFunction::AF6 as u8 }, OpenDrain>> {});
         )+
 
         impl I2cExt<$I2CX> for $I2CX {
@@ -481,14 +501,14 @@ i2c!(
     I2C1,
     i2c1,
     sda: [
-        PA10<Output<OpenDrain>>,
-        PB7<Output<OpenDrain>>,
-        PB9<Output<OpenDrain>>,
+        PA10,
+        PB7,
+        PB9,
     ],
     scl: [
-        PA9<Output<OpenDrain>>,
-        PB6<Output<OpenDrain>>,
-        PB8<Output<OpenDrain>>,
+        PA9,
+        PB6,
+        PB8,
     ],
 );
 
@@ -496,13 +516,13 @@ i2c!(
     I2C2,
     i2c2,
     sda: [
-        PA12<Output<OpenDrain>>,
-        PB11<Output<OpenDrain>>,
-        PB14<Output<OpenDrain>>,
+        PA12,
+        PB11,
+        PB14,
     ],
     scl: [
-        PA11<Output<OpenDrain>>,
-        PB10<Output<OpenDrain>>,
-        PB13<Output<OpenDrain>>,
+        PA11,
+        PB10,
+        PB13,
     ],
 );
@@ -0,0 +1,280 @@
+//! I2C master transfers using the DMA
+use core::marker::PhantomData;
+
+use crate::dma;
+use crate::dmamux::DmaMuxIndex;
+use crate::i2c::{Error, I2c};
+use crate::stm32::{I2C1, I2C2};
+
+/// Largest number of bytes NBYTES can hold, longer transfers are reloaded
+const MAX_NBYTES: usize = 255;
+
+/// Transmit half of the I2C, as seen by the DMA
+pub struct Tx<'a, I2C> {
+    _i2c: PhantomData<&'a mut I2C>,
+}
+
+/// Receive half of the I2C, as seen by the DMA
+pub struct Rx<'a, I2C> {
+    _i2c: PhantomData<&'a mut I2C>,
+}
+
+fn wait_dma<CH: dma::Channel>(channel: &CH) -> Result<(), Error> {
+    loop {
+        if channel.event_occurred(dma::Event::TransferError) {
+            return Err(Error::Dma);
+        }
+        if channel.event_occurred(dma::Event::TransferComplete) {
+            return Ok(());
+        }
+    }
+}
+
+macro_rules! i2c_dma {
+    ($I2CX:ident, $dmamux_rx:ident, $dmamux_tx:ident) => {
+        impl dma::Target for Tx<'_, $I2CX> {
+            fn dmamux(&self) -> DmaMuxIndex {
+                DmaMuxIndex::$dmamux_tx
+            }
+
+            fn enable_dma(&mut self) {
+                // NOTE(unsafe) Tx only exists while the I2c is borrowed mutably
+                let i2c = unsafe { &(*$I2CX::ptr()) };
+                i2c.cr1.modify(|_, w| w.txdmaen().set_bit());
+            }
+
+            fn disable_dma(&mut self) {
+                // NOTE(unsafe) see above
+                let i2c = unsafe { &(*$I2CX::ptr()) };
+                i2c.cr1.modify(|_, w| w.txdmaen().clear_bit());
+            }
+        }
+
+        unsafe impl dma::TargetAddress<dma::MemoryToPeripheral> for Tx<'_, $I2CX> {
+            type MemSize = u8;
+
+            fn address(&self) -> u32 {
+                // NOTE(unsafe) only the address of the register is taken
+                unsafe { &(*$I2CX::ptr()).txdr as *const _ as u32 }
+            }
+        }
+
+        impl dma::Target for Rx<'_, $I2CX> {
+            fn dmamux(&self) -> DmaMuxIndex {
+                DmaMuxIndex::$dmamux_rx
+            }
+
+            fn enable_dma(&mut self) {
+                // NOTE(unsafe) Rx only exists while the I2c is borrowed mutably
+                let i2c = unsafe { &(*$I2CX::ptr()) };
+                i2c.cr1.modify(|_, w| w.rxdmaen().set_bit());
+            }
+
+            fn disable_dma(&mut self) {
+                // NOTE(unsafe) see above
+                let i2c = unsafe { &(*$I2CX::ptr()) };
+                i2c.cr1.modify(|_, w| w.rxdmaen().clear_bit());
+            }
+        }
+
+        unsafe impl dma::TargetAddress<dma::PeripheralToMemory> for Rx<'_, $I2CX> {
+            type MemSize = u8;
+
+            fn address(&self) -> u32 {
+                // NOTE(unsafe) only the address of the register is taken
+                unsafe { &(*$I2CX::ptr()).rxdr as *const _ as u32 }
+            }
+        }
+
+        impl<SDA, SCL> I2c<$I2CX, SDA, SCL> {
+            /// Transmit and receive halves of the I2C as DMA targets, to use
+            /// with `dma::Transfer`
+            ///
+            /// The DMA only moves the data, the I2C transfer which consumes
+            /// or produces it is not started.
+            pub fn split_dma(&mut self) -> (Tx<'_, $I2CX>, Rx<'_, $I2CX>) {
+                (Tx { _i2c: PhantomData }, Rx { _i2c: PhantomData })
+            }
+
+            /// Write `bytes` to the slave at `addr` using the DMA `channel`,
+            /// blocking until done
+            ///
+            /// Transfers longer than 255 bytes are split using the reload mode.
+            pub fn write_dma<CH: dma::Channel>(
+                &mut self,
+                channel: &mut CH,
+                addr: u8,
+                bytes: &[u8],
+            ) -> Result<(), Error> {
+                assert!(!bytes.is_empty());
+
+                // flush i2c tx register
+                self.i2c.isr.write(|w| w.txe().set_bit());
+
+                let mut tx = Tx::<$I2CX> { _i2c: PhantomData };
+                dma::configure(
+                    channel,
+                    &tx,
+                    dma::Direction::FromMemory,
+                    bytes.as_ptr() as u32,
+                    bytes.len(),
+                );
+                dma::start(channel, &mut tx);
+                let result = self.master_dma(channel, addr, false, bytes.len(), true);
+                dma::stop(channel, &mut tx);
+                result
+            }
+
+            /// Read `bytes` from the slave at `addr` using the DMA `channel`,
+            /// blocking until done
+            ///
+            /// Transfers longer than 255 bytes are split using the reload mode.
+            pub fn read_dma<CH: dma::Channel>(
+                &mut self,
+                channel: &mut CH,
+                addr: u8,
+                bytes: &mut [u8],
+            ) -> Result<(), Error> {
+                assert!(!bytes.is_empty());
+
+                // Flush rxdr register
+                let _ = self.i2c.rxdr.read().rxdata().bits();
+
+                let mut rx = Rx::<$I2CX> { _i2c: PhantomData };
+                dma::configure(
+                    channel,
+                    &rx,
+                    dma::Direction::FromPeripheral,
+                    bytes.as_mut_ptr() as u32,
+                    bytes.len(),
+                );
+                dma::start(channel, &mut rx);
+                // The last byte might still be on its way to memory
+                let result = self
+                    .master_dma(channel, addr, true, bytes.len(), true)
+                    .and_then(|_| wait_dma(channel));
+                dma::stop(channel, &mut rx);
+                result
+            }
+
+            /// Write `snd_buffer` to the slave at `addr`, then read
+            /// `rcv_buffer` after a repeated start, using the DMA channels
+            /// `tx_channel` and `rx_channel` and blocking until done
+            ///
+            /// Transfers longer than 255 bytes are split using the reload mode.
+            pub fn write_read_dma<TXCH: dma::Channel, RXCH: dma::Channel>(
+                &mut self,
+                tx_channel: &mut TXCH,
+                rx_channel: &mut RXCH,
+                addr: u8,
+                snd_buffer: &[u8],
+                rcv_buffer: &mut [u8],
+            ) -> Result<(), Error> {
+                assert!(!snd_buffer.is_empty());
+                assert!(!rcv_buffer.is_empty());
+
+                // flush i2c tx register
+                self.i2c.isr.write(|w| w.txe().set_bit());
+
+                let mut tx = Tx::<$I2CX> { _i2c: PhantomData };
+                dma::configure(
+                    tx_channel,
+                    &tx,
+                    dma::Direction::FromMemory,
+                    snd_buffer.as_ptr() as u32,
+                    snd_buffer.len(),
+                );
+                dma::start(tx_channel, &mut tx);
+                // Software end mode, the read continues with a repeated start
+                let result = self.master_dma(tx_channel, addr, false, snd_buffer.len(), false);
+                dma::stop(tx_channel, &mut tx);
+                result?;
+
+                self.read_dma(rx_channel, addr, rcv_buffer)
+            }
+
+            /// Run a master transfer of `len` bytes fed by the DMA, reloading
+            /// NBYTES every 255 bytes
+            ///
+            /// Returns at the STOP condition in automatic end mode, or when
+            /// the transfer is complete in software end mode. A transfer error
+            /// of the DMA `channel` aborts the transfer with a software reset
+            /// of the I2C, which releases the bus.
+            fn master_dma<CH: dma::Channel>(
+                &mut self,
+                channel: &CH,
+                addr: u8,
+                read: bool,
+                len: usize,
+                autoend: bool,
+            ) -> Result<(), Error> {
+                // Wait for any previous address sequence to end automatically.
+                // This could be up to 50% of a bus cycle (ie. up to 0.5/freq)
+                while self.i2c.cr2.read().start().bit_is_set() {}
+
+                let mut remaining = len;
+                let mut chunk = remaining.min(MAX_NBYTES);
+                // Set START and prepare the first chunk of at most 255 bytes
+                self.i2c.cr2.write(|w| unsafe {
+                    w.nbytes()
+                        .bits(chunk as u8)
+                        .sadd()
+                        .bits((addr << 1) as u16)
+                        .add10()
+                        .clear_bit()
+                        .rd_wrn()
+                        .bit(read)
+                        .reload()
+                        .bit(remaining > MAX_NBYTES)
+                        .autoend()
+                        .bit(autoend)
+                        .start()
+                        .set_bit()
+                });
+
+                loop {
+                    if channel.event_occurred(dma::Event::TransferError) {
+                        self.i2c.cr1.modify(|_, w| w.pe().clear_bit());
+                        while self.i2c.cr1.read().pe().bit_is_set() {}
+                        self.i2c.cr1.modify(|_, w| w.pe().set_bit());
+                        return Err(Error::Dma);
+                    }
+
+                    let isr = self.i2c.isr.read();
+
+                    if isr.berr().bit_is_set() {
+                        self.i2c.icr.write(|w| w.berrcf().set_bit());
+                        return Err(Error::BusError);
+                    } else if isr.arlo().bit_is_set() {
+                        self.i2c.icr.write(|w| w.arlocf().set_bit());
+                        return Err(Error::ArbitrationLost);
+                    } else if isr.nackf().bit_is_set() {
+                        self.i2c.icr.write(|w| w.nackcf().set_bit());
+                        // The master sends a STOP automatically after a NACK
+                        while self.i2c.isr.read().stopf().bit_is_clear() {}
+                        self.i2c.icr.write(|w| w.stopcf().set_bit());
+                        return Err(Error::Nack);
+                    } else if isr.tcr().bit_is_set() {
+                        remaining -= chunk;
+                        chunk = remaining.min(MAX_NBYTES);
+                        // Writing NBYTES clears TCR and resumes the transfer
+                        self.i2c.cr2.modify(|_, w| unsafe {
+                            w.nbytes()
+                                .bits(chunk as u8)
+                                .reload()
+                                .bit(remaining > MAX_NBYTES)
+                        });
+                    } else if autoend && isr.stopf().bit_is_set() {
+                        self.i2c.icr.write(|w| w.stopcf().set_bit());
+                        return Ok(());
+                    } else if !autoend && isr.tc().bit_is_set() {
+                        return Ok(());
+                    }
+                }
+            }
+        }
+    };
+}
+
+i2c_dma!(I2C1, I2C1_RX, I2C1_TX);
+i2c_dma!(I2C2, I2C2_RX, I2C2_TX);
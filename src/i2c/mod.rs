@@ -8,9 +8,11 @@ pub mod nonblocking;
 pub use nonblocking::*;
 
 pub mod config;
+pub mod dma;
 
 use crate::rcc::*;
 pub use config::Config;
+pub use dma::{Rx, Tx};
 
 #[derive(Debug, Clone, Copy)]
 pub enum SlaveAddressMask {
@@ -45,6 +47,8 @@ pub enum Error {
     BusError,
     ArbitrationLost,
     IncorrectFrameSize(usize),
+    /// DMA transfer error
+    Dma,
 }
 
 /// I2C SDA pin
@@ -41,6 +41,7 @@ pub use stm32g0::stm32g070 as stm32;
 pub use crate::stm32::interrupt;
 
 pub mod analog;
+mod bcd;
 pub mod crc;
 pub mod dma;
 pub mod dmamux;
@@ -56,6 +57,7 @@ pub mod rng;
 pub mod rtc;
 pub mod serial;
 pub mod spi;
+pub mod tamp;
 pub mod time;
 pub mod timer;
 pub mod watchdog;
@@ -1,10 +1,13 @@
 //! Power control
 
+#[cfg(feature = "stm32g0x1")]
+use crate::exti::{LineControl, LineTrigger};
 use crate::{
     gpio::*,
     rcc::{Enable, Rcc},
     stm32::PWR,
 };
+use cortex_m::{asm, interrupt, peripheral::SCB};
 
 pub enum LowPowerMode {
     StopMode1 = 0b000,
@@ -13,6 +16,37 @@ pub enum LowPowerMode {
     Shutdown = 0b111,
 }
 
+/// Stop mode entered by `Power::enter_stop`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StopMode {
+    /// Main regulator kept on, for the fastest wakeup
+    Stop0 = 0b000,
+    /// Main regulator off, for the lowest consumption
+    Stop1 = 0b001,
+}
+
+/// Programmable voltage detector threshold, see the datasheet for the
+/// voltage of each level
+#[cfg(feature = "stm32g0x1")]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PvdThreshold {
+    /// Only valid as falling threshold
+    VPvd0 = 0b000,
+    VPvd1 = 0b001,
+    VPvd2 = 0b010,
+    VPvd3 = 0b011,
+    VPvd4 = 0b100,
+    VPvd5 = 0b101,
+    VPvd6 = 0b110,
+}
+
+/// Pull applied to a pin in Standby and Shutdown
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StandbyPull {
+    Up,
+    Down,
+}
+
 pub enum PowerMode {
     Run,
     LowPower(LowPowerMode),
@@ -111,6 +145,176 @@ impl Power {
         }
     }
 
+    /// Enter the Stop `mode` until an EXTI interrupt or event wakes the MCU
+    ///
+    /// The MCU wakes up running from HSISYS with HSE and PLL stopped, they
+    /// are restarted and the system clock selected by `Rcc::freeze` is
+    /// switched back before returning. Interrupts are masked meanwhile, the
+    /// one waking the MCU is served once the clocks are restored.
+    pub fn enter_stop(&mut self, scb: &mut SCB, mode: StopMode, rcc: &mut Rcc) {
+        let cr = rcc.cr.read();
+        let (hse, pll) = (cr.hseon().bit_is_set(), cr.pllon().bit_is_set());
+        let sw_bits = rcc.cfgr.read().sw().bits();
+
+        self.rb
+            .cr1
+            .modify(|_, w| unsafe { w.lpms().bits(mode as u8) });
+        scb.set_sleepdeep();
+        // A pending interrupt still wakes the MCU while masked
+        interrupt::free(|_| {
+            asm::dsb();
+            asm::wfi();
+            scb.clear_sleepdeep();
+
+            if hse {
+                rcc.cr.modify(|_, w| w.hseon().set_bit());
+                while rcc.cr.read().hserdy().bit_is_clear() {}
+            }
+            if pll {
+                rcc.cr.modify(|_, w| w.pllon().set_bit());
+                while rcc.cr.read().pllrdy().bit_is_clear() {}
+            }
+            rcc.cfgr.modify(|_, w| unsafe { w.sw().bits(sw_bits) });
+            while rcc.cfgr.read().sws().bits() != sw_bits {}
+        });
+    }
+
+    /// Enter the Standby mode
+    ///
+    /// The wakeup flags are cleared beforehand, otherwise the MCU would not
+    /// enter Standby. The MCU resets on wakeup, with the standby flag set.
+    pub fn enter_standby(&mut self, scb: &mut SCB) -> ! {
+        self.enter_low_power(scb, LowPowerMode::Standby)
+    }
+
+    /// Enter the Shutdown mode
+    ///
+    /// The wakeup flags are cleared beforehand, otherwise the MCU would not
+    /// enter Shutdown. The MCU resets on wakeup.
+    #[cfg(feature = "stm32g0x1")]
+    pub fn enter_shutdown(&mut self, scb: &mut SCB) -> ! {
+        self.enter_low_power(scb, LowPowerMode::Shutdown)
+    }
+
+    fn enter_low_power(&mut self, scb: &mut SCB, mode: LowPowerMode) -> ! {
+        self.rb.scr.write(|w| {
+            w.csbf()
+                .set_bit()
+                .cwuf1()
+                .set_bit()
+                .cwuf2()
+                .set_bit()
+                .cwuf4()
+                .set_bit()
+                .cwuf5()
+                .set_bit()
+                .cwuf6()
+                .set_bit()
+        });
+        self.rb
+            .cr1
+            .modify(|_, w| unsafe { w.lpms().bits(mode as u8) });
+        scb.set_sleepdeep();
+        asm::dsb();
+        loop {
+            asm::wfi();
+        }
+    }
+
+    /// Return to the Sleep mode when leaving the last interrupt handler,
+    /// instead of the thread mode
+    pub fn sleep_on_exit(&mut self, scb: &mut SCB, enable: bool) {
+        if enable {
+            scb.set_sleeponexit();
+        } else {
+            scb.clear_sleeponexit();
+        }
+    }
+
+    /// Enable the programmable voltage detector, which monitors VDD
+    /// against the `falling` threshold when it drops and against the
+    /// `rising` threshold when it recovers
+    ///
+    /// # Panics
+    ///
+    /// Panics if the `rising` threshold is `VPvd0`.
+    #[cfg(feature = "stm32g0x1")]
+    pub fn enable_pvd(&mut self, falling: PvdThreshold, rising: PvdThreshold) {
+        assert!(rising != PvdThreshold::VPvd0);
+        self.rb.cr2.modify(|_, w| unsafe {
+            w.pvdft()
+                .bits(falling as u8)
+                .pvdrt()
+                .bits(rising as u8)
+                .pvde()
+                .set_bit()
+        });
+    }
+
+    #[cfg(feature = "stm32g0x1")]
+    pub fn disable_pvd(&mut self) {
+        self.rb.cr2.modify(|_, w| w.pvde().clear_bit());
+    }
+
+    /// Returns `true` while VDD is below the PVD threshold
+    #[cfg(feature = "stm32g0x1")]
+    pub fn is_pvd_below_threshold(&self) -> bool {
+        self.rb.sr2.read().pvdo().bit_is_set()
+    }
+
+    /// Enable the PVD interrupt on EXTI line 16
+    ///
+    /// The rising edge is triggered when VDD drops below the falling
+    /// threshold, the falling edge when it recovers above the rising
+    /// threshold. The line is accessed through the `EXTI` peripheral or
+    /// its `pvd` line handle.
+    #[cfg(feature = "stm32g0x1")]
+    pub fn listen_pvd<E: LineTrigger<16>>(&mut self, exti: &mut E, edge: SignalEdge) {
+        exti.set_line_trigger(edge);
+        exti.listen_line();
+    }
+
+    #[cfg(feature = "stm32g0x1")]
+    pub fn unlisten_pvd<E: LineControl<16>>(&mut self, exti: &mut E) {
+        exti.unlisten_line();
+    }
+
+    /// Select the pull applied to the `pin` in Standby and Shutdown, or
+    /// leave it floating with `None`
+    ///
+    /// The pulls only take effect once enabled with `apply_standby_pulls`.
+    pub fn set_standby_pull<PIN: PinId>(&mut self, _pin: &PIN, pull: Option<StandbyPull>) {
+        let mask = 1 << PIN::PIN;
+        let (up, down) = match pull {
+            Some(StandbyPull::Up) => (mask, 0),
+            Some(StandbyPull::Down) => (0, mask),
+            None => (0, 0),
+        };
+        macro_rules! set_pull {
+            ($pucr:ident, $pdcr:ident) => {{
+                self.rb
+                    .$pucr
+                    .modify(|r, w| unsafe { w.bits(r.bits() & !mask | up) });
+                self.rb
+                    .$pdcr
+                    .modify(|r, w| unsafe { w.bits(r.bits() & !mask | down) });
+            }};
+        }
+        match PIN::PORT {
+            0 => set_pull!(pucra, pdcra),
+            1 => set_pull!(pucrb, pdcrb),
+            2 => set_pull!(pucrc, pdcrc),
+            3 => set_pull!(pucrd, pdcrd),
+            _ => set_pull!(pucrf, pdcrf),
+        }
+    }
+
+    /// Apply the pulls selected with `set_standby_pull` in Standby and
+    /// Shutdown, instead of leaving all the pins floating
+    pub fn apply_standby_pulls(&mut self, enable: bool) {
+        self.rb.cr3.modify(|_, w| w.apc().bit(enable));
+    }
+
     pub fn set_mode(&mut self, mode: PowerMode) {
         match mode {
             PowerMode::Run => {
@@ -24,6 +24,8 @@ pub use crate::dma::DmaExt as _;
 pub use crate::exti::ExtiExt as _;
 pub use crate::flash::FlashExt as _;
 pub use crate::gpio::GpioExt as _;
+pub use crate::gpio::PortReader as _;
+pub use crate::gpio::PortWriter as _;
 pub use crate::i2c::I2cExt as _;
 pub use crate::power::PowerExt as _;
 pub use crate::rcc::LSCOExt as _;
@@ -36,6 +38,7 @@ pub use crate::rng::RngExt as _;
 pub use crate::rtc::RtcExt as _;
 pub use crate::serial::SerialExt as _;
 pub use crate::spi::SpiExt as _;
+pub use crate::tamp::TampExt as _;
 pub use crate::time::U32Ext as _;
 pub use crate::timer::opm::OpmExt as _;
 pub use crate::timer::pwm::PwmExt as _;
@@ -55,6 +55,8 @@ pub enum PLLSrc {
 pub enum RTCSrc {
     LSE = 0b01,
     LSI = 0b10,
+    /// HSE divided by 32, the HSE frequency being the one configured with
+    /// `Rcc::freeze`
     HSE = 0b11,
 }
 
@@ -27,6 +27,8 @@ pub struct Clocks {
     pub apb_tim_clk: Hertz,
     /// PLL frequency
     pub pll_clk: PLLClocks,
+    /// HSE frequency, if configured
+    pub hse_clk: Option<Hertz>,
 }
 
 /// PLL Clock frequencies
@@ -53,6 +55,7 @@ impl Default for Clocks {
                 q: None,
                 p: None,
             },
+            hse_clk: None,
         }
     }
 }
@@ -87,6 +90,13 @@ impl Rcc {
     /// Apply clock configuration
     pub fn freeze(self, rcc_cfg: Config) -> Self {
         let pll_clk = self.config_pll(rcc_cfg.pll_cfg);
+        let hse_clk = match (&rcc_cfg.sys_mux, &rcc_cfg.pll_cfg.mux) {
+            (SysClockSrc::HSE(freq), _)
+            | (SysClockSrc::HSE_BYPASS(freq), _)
+            | (_, PLLSrc::HSE(freq))
+            | (_, PLLSrc::HSE_BYPASS(freq)) => Some(*freq),
+            _ => None,
+        };
 
         let (sys_clk, sw_bits) = match rcc_cfg.sys_mux {
             SysClockSrc::HSE(freq) => {
@@ -181,6 +191,7 @@ impl Rcc {
                 ahb_clk: ahb_freq.Hz(),
                 apb_clk: apb_freq.Hz(),
                 apb_tim_clk: apb_tim_freq.Hz(),
+                hse_clk,
             },
         }
     }
@@ -300,6 +311,8 @@ impl Rcc {
         while pwr.cr1.read().dbp().bit_is_clear() {}
     }
 
+    /// Enable the RTC clocked by `src`, resetting the backup domain unless
+    /// the RTC already runs from the same source
     pub(crate) fn enable_rtc(&self, src: RTCSrc) {
         match src {
             RTCSrc::LSI => self.enable_lsi(),
@@ -310,6 +323,10 @@ impl Rcc {
             .modify(|_, w| w.rtcapben().set_bit().pwren().set_bit());
         self.apbsmenr1.modify(|_, w| w.rtcapbsmen().set_bit());
         self.unlock_rtc();
+        let bdcr = self.bdcr.read();
+        if bdcr.rtcen().bit_is_set() && bdcr.rtcsel().bits() == src as u8 {
+            return;
+        }
         self.bdcr.modify(|_, w| w.bdrst().set_bit());
         self.bdcr.modify(|_, w| unsafe {
             w.rtcsel()
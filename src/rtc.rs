@@ -1,22 +1,292 @@
 //! Real Time Clock
+use crate::bcd;
+use crate::exti::LineControl;
 use crate::rcc::{RTCSrc, Rcc};
 use crate::stm32::RTC;
 use crate::time::*;
 
+/// RTC interrupt events
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Event {
+    AlarmA,
+    AlarmB,
+    WakeupTimer,
+    Timestamp,
+}
+
+/// Time and date captured on a timestamp event
+///
+/// The year is not captured.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Timestamp {
+    pub time: Time,
+    pub subseconds: u16,
+    pub day: u32,
+    pub month: u32,
+    pub week_day: u8,
+}
+
+/// Hour format of the calendar
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HourFormat {
+    /// 24-hour clock
+    H24,
+    /// 12-hour clock with AM/PM
+    H12,
+}
+
+/// RTC alarm
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Alarm {
+    A,
+    B,
+}
+
+/// Day matched by an alarm
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AlarmDay {
+    /// Day of the month (1-31)
+    Date(MonthDay),
+    /// Day of the week (1-7)
+    WeekDay(WeekDay),
+}
+
+/// Alarm configuration
+///
+/// Fields which are not set are masked and match any value, so the default
+/// configuration triggers the alarm every second.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct AlarmConfig {
+    day: Option<AlarmDay>,
+    hours: Option<u32>,
+    minutes: Option<u32>,
+    seconds: Option<u32>,
+    subseconds: u32,
+    subseconds_bits: u8,
+}
+
+impl AlarmConfig {
+    /// Match the `day`
+    ///
+    /// # Panics
+    ///
+    /// Panics if the day of the month is not within 1-31, or the day of the
+    /// week not within 1-7.
+    pub fn day(mut self, day: AlarmDay) -> Self {
+        match day {
+            AlarmDay::Date(day) => assert!((1..=31).contains(&day.0)),
+            AlarmDay::WeekDay(day) => assert!((1..=7).contains(&day.0)),
+        }
+        self.day = Some(day);
+        self
+    }
+
+    /// Match the `hours`, from 0 to 23
+    ///
+    /// # Panics
+    ///
+    /// Panics if `hours` is larger than 23.
+    pub fn hours(mut self, hours: Hour) -> Self {
+        assert!(hours.ticks() < 24);
+        self.hours = Some(hours.ticks());
+        self
+    }
+
+    /// Match the `minutes`
+    ///
+    /// # Panics
+    ///
+    /// Panics if `minutes` is larger than 59.
+    pub fn minutes(mut self, minutes: Minute) -> Self {
+        assert!(minutes.ticks() < 60);
+        self.minutes = Some(minutes.ticks());
+        self
+    }
+
+    /// Match the `seconds`
+    ///
+    /// # Panics
+    ///
+    /// Panics if `seconds` is larger than 59.
+    pub fn seconds(mut self, seconds: Second) -> Self {
+        assert!(seconds.ticks() < 60);
+        self.seconds = Some(seconds.ticks());
+        self
+    }
+
+    /// Match the hours, minutes and seconds of `time`
+    pub fn time(self, time: &Time) -> Self {
+        self.hours(time.hours.hours())
+            .minutes(time.minutes.minutes())
+            .seconds(time.seconds.secs())
+    }
+
+    /// Match the `bits` least significant bits (0 to 15) of the subsecond
+    /// counter with `subseconds`
+    ///
+    /// # Panics
+    ///
+    /// Panics if `bits` is larger than 15, or `subseconds` does not fit in
+    /// the 15-bit subsecond counter.
+    pub fn subseconds(mut self, subseconds: u32, bits: u8) -> Self {
+        assert!(bits <= 15);
+        assert!(subseconds <= 0x7fff);
+        self.subseconds = subseconds;
+        self.subseconds_bits = bits;
+        self
+    }
+}
+
 pub struct Rtc {
     rb: RTC,
 }
 
 impl Rtc {
+    /// Enable the RTC clocked by `src`
+    ///
+    /// An RTC already running from `src` keeps its calendar, configuration
+    /// and backup registers, e.g. after waking up from Standby. Otherwise
+    /// the backup domain is reset.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `src` is the HSE but the RCC was not frozen with an HSE
+    /// clock, whose frequency is then unknown.
     pub fn new(rtc: RTC, src: RTCSrc, rcc: &mut Rcc) -> Self {
+        let rtc_clk = match src {
+            RTCSrc::LSE => 32_768,
+            RTCSrc::LSI => 32_000,
+            RTCSrc::HSE => rcc.clocks.hse_clk.expect("HSE not configured").raw() / 32,
+        };
         let mut rtc = Rtc { rb: rtc };
         rcc.enable_rtc(src);
-        rtc.modify(|rb| {
-            rb.cr.modify(|_, w| w.fmt().clear_bit());
-        });
+        if rtc.rb.icsr.read().inits().bit_is_clear() {
+            let (prediv_a, prediv_s) = prescalers(rtc_clk);
+            rtc.modify(|rb| {
+                rb.cr.modify(|_, w| w.fmt().clear_bit());
+                rb.prer
+                    .write(|w| unsafe { w.prediv_a().bits(prediv_a).prediv_s().bits(prediv_s) });
+            });
+        }
         rtc
     }
 
+    /// Set the asynchronous and synchronous prescalers dividing RTCCLK down
+    /// to the 1 Hz calendar clock: ck_spre = RTCCLK / ((PREDIV_A + 1) * (PREDIV_S + 1))
+    ///
+    /// `Rtc::new` already selects prescalers for the RTC clock source,
+    /// favoring a large `prediv_a` to lower the consumption.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `prediv_a` is larger than 127 or `prediv_s` larger than 32767.
+    pub fn set_prescalers(&mut self, prediv_a: u8, prediv_s: u16) {
+        assert!(prediv_a <= 0x7f);
+        assert!(prediv_s <= 0x7fff);
+        self.modify(|rb| {
+            rb.prer
+                .write(|w| unsafe { w.prediv_a().bits(prediv_a).prediv_s().bits(prediv_s) });
+        });
+    }
+
+    /// Select the hour format of the calendar
+    ///
+    /// `Time` always holds 24-hour values, they are converted when accessing
+    /// the calendar, the alarms and the timestamp. The time must be set
+    /// again after changing the format.
+    pub fn set_hour_format(&mut self, format: HourFormat) {
+        self.modify(|rb| rb.cr.modify(|_, w| w.fmt().bit(format == HourFormat::H12)));
+    }
+
+    pub fn hour_format(&self) -> HourFormat {
+        if self.rb.cr.read().fmt().bit_is_set() {
+            HourFormat::H12
+        } else {
+            HourFormat::H24
+        }
+    }
+
+    /// Subsecond counter, counting down from PREDIV_S to 0 within each second
+    pub fn get_subseconds(&self) -> u16 {
+        let ss = self.rb.ssr.read().ss().bits();
+        // Reading SSR freezes TR and DR until DR is read
+        let _ = self.rb.dr.read();
+        ss
+    }
+
+    /// Milliseconds elapsed in the current second
+    pub fn get_milliseconds(&self) -> u32 {
+        self.subseconds_to_ms(self.get_subseconds())
+    }
+
+    /// Read the time with a millisecond resolution
+    ///
+    /// The subseconds and the time are read consistently, no second can
+    /// elapse in between.
+    pub fn get_time_ms(&self) -> (Time, u32) {
+        // Reading SSR freezes TR and DR until DR is read
+        let ss = self.rb.ssr.read().ss().bits();
+        let time = self.get_time();
+        let _ = self.rb.dr.read();
+        (time, self.subseconds_to_ms(ss))
+    }
+
+    fn subseconds_to_ms(&self, ss: u16) -> u32 {
+        let prediv_s = self.rb.prer.read().prediv_s().bits() as u32;
+        // The counter goes beyond PREDIV_S right after a shift
+        let elapsed = prediv_s.saturating_sub(ss as u32);
+        elapsed * 1000 / (prediv_s + 1)
+    }
+
+    /// Apply a smooth digital calibration of `ppm` parts per million, from
+    /// -487 to +488, a positive value speeding up a slow clock
+    ///
+    /// # Panics
+    ///
+    /// Panics if `ppm` is out of range.
+    pub fn set_calibration(&mut self, ppm: i32) {
+        assert!((-487..=488).contains(&ppm));
+        // The calibration cycle is 2^20 RTCCLK periods. CALP inserts 512
+        // pulses, CALM masks up to 511 pulses.
+        let pulses = ppm * (1 << 20) / 1_000_000;
+        let (calp, calm) = if pulses > 0 {
+            (true, (512 - pulses) as u16)
+        } else {
+            (false, (-pulses) as u16)
+        };
+        self.unprotected(|rb| {
+            while rb.icsr.read().recalpf().bit_is_set() {}
+            rb.calr
+                .write(|w| unsafe { w.calp().bit(calp).calm().bits(calm) });
+        });
+    }
+
+    /// Shift the clock by `ticks` periods of the subsecond counter, to
+    /// synchronize it with an external time source
+    ///
+    /// A positive value advances the clock, a negative value delays it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `ticks` is larger than PREDIV_S + 1 or smaller than
+    /// -PREDIV_S.
+    pub fn shift(&mut self, ticks: i32) {
+        let second = self.rb.prer.read().prediv_s().bits() as i32 + 1;
+        assert!((1 - second..=second).contains(&ticks));
+        // The shift adds one second and/or subtracts a fraction of a second
+        let (add1s, subfs) = if ticks > 0 {
+            (true, (second - ticks) as u16)
+        } else {
+            (false, (-ticks) as u16)
+        };
+        self.unprotected(|rb| {
+            while rb.icsr.read().shpf().bit_is_set() {}
+            rb.shiftr
+                .write(|w| unsafe { w.add1s().bit(add1s).subfs().bits(subfs) });
+        });
+        while self.rb.icsr.read().shpf().bit_is_set() {}
+    }
+
     fn modify<F>(&mut self, mut closure: F)
     where
         F: FnMut(&mut RTC),
@@ -39,10 +309,28 @@ impl Rtc {
         self.rb.wpr.write(|w| unsafe { w.bits(0xFF) });
     }
 
+    /// Run `closure` with the write protection disabled, without entering the
+    /// initialization mode
+    fn unprotected<F>(&mut self, mut closure: F)
+    where
+        F: FnMut(&mut RTC),
+    {
+        self.rb.wpr.write(|w| unsafe { w.bits(0xCA) });
+        self.rb.wpr.write(|w| unsafe { w.bits(0x53) });
+        closure(&mut self.rb);
+        self.rb.wpr.write(|w| unsafe { w.bits(0xFF) });
+    }
+
+    /// Set the calendar date, the week day is computed from it
+    ///
+    /// # Panics
+    ///
+    /// Panics unless the year is within 2000-2099.
     pub fn set_date(&mut self, date: &Date) {
-        let (yt, yu) = bcd2_encode(date.year - 1970);
-        let (mt, mu) = bcd2_encode(date.month);
-        let (dt, du) = bcd2_encode(date.day);
+        assert!((2000..2100).contains(&date.year));
+        let (yt, yu) = bcd::encode(date.year - 2000);
+        let (mt, mu) = bcd::encode(date.month);
+        let (dt, du) = bcd::encode(date.day);
 
         self.modify(|rb| {
             rb.dr.write(|w| unsafe {
@@ -59,15 +347,16 @@ impl Rtc {
                     .yu()
                     .bits(yu)
                     .wdu()
-                    .bits(date.day as u8)
+                    .bits(date.week_day().0 as u8)
             });
         });
     }
 
+    /// Set the time, along with the daylight saving time memo
     pub fn set_time(&mut self, time: &Time) {
-        let (ht, hu) = bcd2_encode(time.hours);
-        let (mnt, mnu) = bcd2_encode(time.minutes);
-        let (st, su) = bcd2_encode(time.seconds);
+        let (pm, ht, hu) = encode_hours(self.hour_format(), time.hours);
+        let (mnt, mnu) = bcd::encode(time.minutes);
+        let (st, su) = bcd::encode(time.seconds);
         self.modify(|rb| {
             rb.tr.write(|w| unsafe {
                 w.ht()
@@ -83,34 +372,277 @@ impl Rtc {
                     .su()
                     .bits(su)
                     .pm()
-                    .clear_bit()
+                    .bit(pm)
             });
-            rb.cr.modify(|_, w| w.fmt().bit(time.daylight_savings));
+            rb.cr.modify(|_, w| w.bkp().bit(time.daylight_savings));
         });
     }
 
     pub fn get_time(&self) -> Time {
         let timer = self.rb.tr.read();
         Time::new(
-            bcd2_decode(timer.ht().bits(), timer.hu().bits()).hours(),
-            bcd2_decode(timer.mnt().bits(), timer.mnu().bits()).minutes(),
-            bcd2_decode(timer.st().bits(), timer.su().bits()).secs(),
-            self.rb.cr.read().fmt().bit(),
+            decode_hours(
+                self.hour_format(),
+                timer.pm().bit(),
+                timer.ht().bits(),
+                timer.hu().bits(),
+            )
+            .hours(),
+            bcd::decode(timer.mnt().bits(), timer.mnu().bits()).minutes(),
+            bcd::decode(timer.st().bits(), timer.su().bits()).secs(),
+            self.is_daylight_saving_time(),
         )
     }
 
+    /// Move the clock forward by one hour and set the daylight saving time
+    /// memo
+    pub fn enter_daylight_saving_time(&mut self) {
+        self.unprotected(|rb| rb.cr.modify(|_, w| w.add1h().set_bit().bkp().set_bit()));
+    }
+
+    /// Move the clock back by one hour and clear the daylight saving time
+    /// memo
+    ///
+    /// The clock is not moved back when it is between midnight and 1 AM.
+    pub fn exit_daylight_saving_time(&mut self) {
+        self.unprotected(|rb| rb.cr.modify(|_, w| w.sub1h().set_bit().bkp().clear_bit()));
+    }
+
+    /// Daylight saving time memo
+    pub fn is_daylight_saving_time(&self) -> bool {
+        self.rb.cr.read().bkp().bit_is_set()
+    }
+
     pub fn get_date(&self) -> Date {
         let date = self.rb.dr.read();
-        Date::new(
-            (bcd2_decode(date.yt().bits(), date.yu().bits()) + 1970).year(),
-            bcd2_decode(date.mt().bit() as u8, date.mu().bits()).month(),
-            bcd2_decode(date.dt().bits(), date.du().bits()).day(),
-        )
+        // The calendar counts years 2000-2099, with leap years divisible by 4
+        Date {
+            day: bcd::decode(date.dt().bits(), date.du().bits()),
+            month: bcd::decode(date.mt().bit() as u8, date.mu().bits()),
+            year: bcd::decode(date.yt().bits(), date.yu().bits()) + 2000,
+        }
     }
 
     pub fn get_week_day(&self) -> u8 {
         self.rb.dr.read().wdu().bits()
     }
+
+    /// Configure and enable the `alarm`
+    pub fn set_alarm(&mut self, alarm: Alarm, config: AlarmConfig) {
+        let (dt, du, wdsel, msk4) = match config.day {
+            Some(AlarmDay::Date(day)) => {
+                let (dt, du) = bcd::encode(day.0);
+                (dt, du, false, false)
+            }
+            Some(AlarmDay::WeekDay(day)) => (0, day.0 as u8, true, false),
+            None => (0, 0, false, true),
+        };
+        let (pm, ht, hu) = encode_hours(self.hour_format(), config.hours.unwrap_or(0));
+        let (mnt, mnu) = bcd::encode(config.minutes.unwrap_or(0));
+        let (st, su) = bcd::encode(config.seconds.unwrap_or(0));
+
+        self.disable_alarm(alarm);
+        self.unprotected(|rb| {
+            macro_rules! write_alarm {
+                ($alrmr:ident, $alrmssr:ident, $alrwf:ident, $alre:ident) => {{
+                    while rb.icsr.read().$alrwf().bit_is_clear() {}
+                    rb.$alrmr.write(|w| unsafe {
+                        w.msk4()
+                            .bit(msk4)
+                            .wdsel()
+                            .bit(wdsel)
+                            .dt()
+                            .bits(dt)
+                            .du()
+                            .bits(du)
+                            .msk3()
+                            .bit(config.hours.is_none())
+                            .pm()
+                            .bit(pm)
+                            .ht()
+                            .bits(ht)
+                            .hu()
+                            .bits(hu)
+                            .msk2()
+                            .bit(config.minutes.is_none())
+                            .mnt()
+                            .bits(mnt)
+                            .mnu()
+                            .bits(mnu)
+                            .msk1()
+                            .bit(config.seconds.is_none())
+                            .st()
+                            .bits(st)
+                            .su()
+                            .bits(su)
+                    });
+                    rb.$alrmssr.write(|w| unsafe {
+                        w.maskss()
+                            .bits(config.subseconds_bits)
+                            .ss()
+                            .bits(config.subseconds as u16)
+                    });
+                    rb.cr.modify(|_, w| w.$alre().set_bit());
+                }};
+            }
+            match alarm {
+                Alarm::A => write_alarm!(alrmar, alrmassr, alrawf, alrae),
+                Alarm::B => write_alarm!(alrmbr, alrmbssr, alrbwf, alrbe),
+            }
+        });
+    }
+
+    /// Disable the `alarm`
+    pub fn disable_alarm(&mut self, alarm: Alarm) {
+        self.unprotected(|rb| match alarm {
+            Alarm::A => rb.cr.modify(|_, w| w.alrae().clear_bit()),
+            Alarm::B => rb.cr.modify(|_, w| w.alrbe().clear_bit()),
+        });
+    }
+
+    /// Enable the interrupt of the `event` and its EXTI line, which wakes
+    /// the MCU from Stop and Standby
+    ///
+    /// The line is accessed through the `EXTI` peripheral or its `rtc` line
+    /// handle.
+    pub fn listen<E: LineControl<19>>(&mut self, exti: &mut E, event: Event) {
+        self.unprotected(|rb| match event {
+            Event::AlarmA => rb.cr.modify(|_, w| w.alraie().set_bit()),
+            Event::AlarmB => rb.cr.modify(|_, w| w.alrbie().set_bit()),
+            Event::WakeupTimer => rb.cr.modify(|_, w| w.wutie().set_bit()),
+            Event::Timestamp => rb.cr.modify(|_, w| w.tsie().set_bit()),
+        });
+        exti.listen_line();
+    }
+
+    /// Disable the interrupt of the `event`, and the EXTI line once no RTC
+    /// interrupt is enabled anymore
+    pub fn unlisten<E: LineControl<19>>(&mut self, exti: &mut E, event: Event) {
+        self.unprotected(|rb| match event {
+            Event::AlarmA => rb.cr.modify(|_, w| w.alraie().clear_bit()),
+            Event::AlarmB => rb.cr.modify(|_, w| w.alrbie().clear_bit()),
+            Event::WakeupTimer => rb.cr.modify(|_, w| w.wutie().clear_bit()),
+            Event::Timestamp => rb.cr.modify(|_, w| w.tsie().clear_bit()),
+        });
+        let cr = self.rb.cr.read();
+        if cr.alraie().bit_is_clear()
+            && cr.alrbie().bit_is_clear()
+            && cr.wutie().bit_is_clear()
+            && cr.tsie().bit_is_clear()
+        {
+            exti.unlisten_line();
+        }
+    }
+
+    /// Has the `event` occurred?
+    pub fn is_pending(&self, event: Event) -> bool {
+        let sr = self.rb.sr.read();
+        match event {
+            Event::AlarmA => sr.alraf().bit_is_set(),
+            Event::AlarmB => sr.alrbf().bit_is_set(),
+            Event::WakeupTimer => sr.wutf().bit_is_set(),
+            Event::Timestamp => sr.tsf().bit_is_set(),
+        }
+    }
+
+    /// Clear the flag of the `event`
+    pub fn unpend(&mut self, event: Event) {
+        match event {
+            Event::AlarmA => self.rb.scr.write(|w| w.calraf().set_bit()),
+            Event::AlarmB => self.rb.scr.write(|w| w.calrbf().set_bit()),
+            Event::WakeupTimer => self.rb.scr.write(|w| w.cwutf().set_bit()),
+            Event::Timestamp => self.rb.scr.write(|w| w.ctsf().set_bit().ctsovf().set_bit()),
+        }
+    }
+
+    /// Configure and enable the wakeup timer to expire every `period`
+    ///
+    /// Periods up to 32 s at 32.768 kHz are counted with the finest RTCCLK
+    /// divider which fits, longer ones with the 1 Hz calendar clock and a
+    /// resolution of one second, up to 36 h 24 min 32 s.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `period` is shorter than one tick of RTCCLK/4, or longer
+    /// than 131072 s.
+    pub fn set_wakeup_timer(&mut self, period: MicroSecond) {
+        let us = period.ticks() as u64;
+        let rtc_clk = self.rtc_clk() as u64;
+        // RTCCLK/2, /4, /8 and /16, then ck_spre, then ck_spre with 2^16
+        // added to the counter. WUT = 0 is not allowed with RTCCLK/2.
+        let (wucksel, ticks) = match (0..4)
+            .rev()
+            .map(|sel| (sel, us * rtc_clk / ((16 >> sel) * 1_000_000)))
+            .find(|&(sel, ticks)| ticks <= 0x1_0000 && (sel != 0b011 || ticks > 1))
+        {
+            Some(setting) => setting,
+            None => match us / 1_000_000 {
+                secs if secs <= 0x1_0000 => (0b100, secs),
+                secs => (0b110, secs - 0x1_0000),
+            },
+        };
+        assert!(ticks > 0 && ticks <= 0x1_0000);
+
+        self.disable_wakeup_timer();
+        self.unprotected(|rb| {
+            while rb.icsr.read().wutwf().bit_is_clear() {}
+            rb.wutr
+                .write(|w| unsafe { w.wut().bits((ticks - 1) as u16) });
+            rb.cr
+                .modify(|_, w| unsafe { w.wucksel().bits(wucksel).wute().set_bit() });
+        });
+    }
+
+    /// Capture a timestamp when a tamper event is detected by the TAMP
+    /// peripheral
+    pub fn enable_timestamp_on_tamper(&mut self, enable: bool) {
+        self.unprotected(|rb| rb.cr.modify(|_, w| w.tampts().bit(enable)));
+    }
+
+    /// Read and clear the captured timestamp, if any
+    ///
+    /// Later timestamps are lost until it is read, which is reported by
+    /// the overflow flag cleared along with it.
+    pub fn get_timestamp(&mut self) -> Option<Timestamp> {
+        if self.rb.sr.read().tsf().bit_is_clear() {
+            return None;
+        }
+        let subseconds = self.rb.tsssr.read().ss().bits();
+        let tstr = self.rb.tstr.read();
+        let tsdr = self.rb.tsdr.read();
+        let timestamp = Timestamp {
+            time: Time::new(
+                decode_hours(
+                    self.hour_format(),
+                    tstr.pm().bit(),
+                    tstr.ht().bits(),
+                    tstr.hu().bits(),
+                )
+                .hours(),
+                bcd::decode(tstr.mnt().bits(), tstr.mnu().bits()).minutes(),
+                bcd::decode(tstr.st().bits(), tstr.su().bits()).secs(),
+                self.is_daylight_saving_time(),
+            ),
+            subseconds,
+            day: bcd::decode(tsdr.dt().bits(), tsdr.du().bits()),
+            month: bcd::decode(tsdr.mt().bit() as u8, tsdr.mu().bits()),
+            week_day: tsdr.wdu().bits(),
+        };
+        self.unpend(Event::Timestamp);
+        Some(timestamp)
+    }
+
+    /// Disable the wakeup timer
+    pub fn disable_wakeup_timer(&mut self) {
+        self.unprotected(|rb| rb.cr.modify(|_, w| w.wute().clear_bit()));
+    }
+
+    /// RTCCLK frequency in Hz, as assumed by the prescalers which divide it
+    /// down to the 1 Hz calendar clock
+    fn rtc_clk(&self) -> u32 {
+        let prer = self.rb.prer.read();
+        (prer.prediv_a().bits() as u32 + 1) * (prer.prediv_s().bits() as u32 + 1)
+    }
 }
 
 pub trait RtcExt {
@@ -123,19 +655,73 @@ impl RtcExt for RTC {
     }
 }
 
-fn bcd2_encode(word: u32) -> (u8, u8) {
-    let mut value = word as u8;
-    let mut bcd_high: u8 = 0;
-    while value >= 10 {
-        bcd_high += 1;
-        value -= 10;
+/// Prescalers dividing `rtc_clk` down to 1 Hz, with the largest possible
+/// asynchronous prescaler
+fn prescalers(rtc_clk: u32) -> (u8, u16) {
+    let prediv_a = (1..=128)
+        .rev()
+        .find(|a| rtc_clk / a <= 0x8000 && rtc_clk / a * a == rtc_clk)
+        .unwrap_or(128);
+    let prediv_s = (rtc_clk / prediv_a).clamp(1, 0x8000);
+    ((prediv_a - 1) as u8, (prediv_s - 1) as u16)
+}
+
+/// Encode 24-hour `hours` into the PM flag and BCD digits of the hour
+/// `format`
+fn encode_hours(format: HourFormat, hours: u32) -> (bool, u8, u8) {
+    match format {
+        HourFormat::H24 => {
+            let (ht, hu) = bcd::encode(hours);
+            (false, ht, hu)
+        }
+        HourFormat::H12 => {
+            let (ht, hu) = bcd::encode(match hours % 12 {
+                0 => 12,
+                hours => hours,
+            });
+            (hours >= 12, ht, hu)
+        }
+    }
+}
+
+/// Decode hours of the hour `format` into 24-hour hours
+fn decode_hours(format: HourFormat, pm: bool, ht: u8, hu: u8) -> u32 {
+    let hours = bcd::decode(ht, hu);
+    match format {
+        HourFormat::H24 => hours,
+        HourFormat::H12 => hours % 12 + if pm { 12 } else { 0 },
     }
-    let bcd_low = ((bcd_high << 4) | value) as u8;
-    (bcd_high, bcd_low)
 }
 
-fn bcd2_decode(fst: u8, snd: u8) -> u32 {
-    let value = snd | fst << 4;
-    let value = (value & 0x0F) + ((value & 0xF0) >> 4) * 10;
-    value as u32
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hours_round_trip() {
+        for format in [HourFormat::H24, HourFormat::H12] {
+            for hours in 0..24 {
+                let (pm, ht, hu) = encode_hours(format, hours);
+                assert_eq!(decode_hours(format, pm, ht, hu), hours);
+            }
+        }
+    }
+
+    #[test]
+    fn hours_12h() {
+        assert_eq!(encode_hours(HourFormat::H12, 0), (false, 1, 2));
+        assert_eq!(encode_hours(HourFormat::H12, 11), (false, 1, 1));
+        assert_eq!(encode_hours(HourFormat::H12, 12), (true, 1, 2));
+        assert_eq!(encode_hours(HourFormat::H12, 13), (true, 0, 1));
+        assert_eq!(encode_hours(HourFormat::H12, 23), (true, 1, 1));
+    }
+
+    #[test]
+    fn hours_24h() {
+        assert_eq!(encode_hours(HourFormat::H24, 0), (false, 0, 0));
+        assert_eq!(encode_hours(HourFormat::H24, 11), (false, 1, 1));
+        assert_eq!(encode_hours(HourFormat::H24, 12), (false, 1, 2));
+        assert_eq!(encode_hours(HourFormat::H24, 13), (false, 1, 3));
+        assert_eq!(encode_hours(HourFormat::H24, 23), (false, 2, 3));
+    }
 }
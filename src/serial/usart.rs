@@ -220,6 +220,8 @@ macro_rules! uart_shared {
                     self
                 }
             }
+
+            locked_pin!(impl<OTYPE> TxPin<$USARTX> for $PTX<Alternate<{ $TAF as u8 }, OTYPE>> {});
         )+
 
         $(
@@ -232,6 +234,8 @@ macro_rules! uart_shared {
                     self
                 }
             }
+
+            locked_pin!(impl<OTYPE> RxPin<$USARTX> for $PRX<Alternate<{ $RAF as u8 }, OTYPE>> {});
         )+
 
         $(
@@ -244,6 +248,8 @@ macro_rules! uart_shared {
                     self
                 }
             }
+
+            locked_pin!(impl<OTYPE> DriverEnablePin<$USARTX> for $PDE<Alternate<{ $DAF as u8 }, OTYPE>> {});
         )+
 
         impl<Config> Rx<$USARTX, Config> {
@@ -264,6 +270,42 @@ macro_rules! uart_shared {
                 usart.isr.read().rxne().bit_is_set()
             }
 
+            /// Starts listening for the idle line interrupt
+            pub fn listen_idle(&mut self) {
+                let usart = unsafe { &(*$USARTX::ptr()) };
+                usart.cr1.modify(|_, w| w.idleie().set_bit());
+            }
+
+            /// Stop listening for the idle line interrupt
+            pub fn unlisten_idle(&mut self) {
+                let usart = unsafe { &(*$USARTX::ptr()) };
+                usart.cr1.modify(|_, w| w.idleie().clear_bit());
+            }
+
+            /// Return true if an idle line has been detected
+            pub fn is_idle(&self) -> bool {
+                let usart = unsafe { &(*$USARTX::ptr()) };
+                usart.isr.read().idle().bit_is_set()
+            }
+
+            /// Clear pending idle line interrupt
+            pub fn clear_idle(&mut self) {
+                let usart = unsafe { &(*$USARTX::ptr()) };
+                usart.icr.write(|w| w.idlecf().set_bit());
+            }
+
+            /// Receive continuously into a ring buffer using the DMA `channel`
+            ///
+            /// Combined with the idle line or the receiver timeout interrupt,
+            /// this allows to process messages of unknown length as soon as
+            /// they are complete.
+            pub fn into_ring_buffer<CH: dma::Channel, const N: usize>(
+                self,
+                channel: CH,
+                buffer: &'static mut [u8; N],
+            ) -> dma::RingBuffer<CH, Self, u8, N> {
+                dma::RingBuffer::new(channel, self, buffer)
+            }
         }
 
         impl<Config> hal::serial::Read<u8> for Rx<$USARTX, Config> {
@@ -393,6 +435,15 @@ macro_rules! uart_shared {
             }
         }
 
+        unsafe impl<Config> dma::TargetAddress<dma::PeripheralToMemory> for Rx<$USARTX, Config> {
+            type MemSize = u8;
+
+            fn address(&self) -> u32 {
+                // NOTE(unsafe) only the address of the register is taken
+                unsafe { &(*$USARTX::ptr()).rdr as *const _ as u32 }
+            }
+        }
+
         impl<Config> dma::Target for Tx<$USARTX, Config> {
 
             fn dmamux(&self) -> DmaMuxIndex {
@@ -415,6 +466,15 @@ macro_rules! uart_shared {
                 });
             }
         }
+
+        unsafe impl<Config> dma::TargetAddress<dma::MemoryToPeripheral> for Tx<$USARTX, Config> {
+            type MemSize = u8;
+
+            fn address(&self) -> u32 {
+                // NOTE(unsafe) only the address of the register is taken
+                unsafe { &(*$USARTX::ptr()).tdr as *const _ as u32 }
+            }
+        }
     }
 }
 
@@ -1,7 +1,10 @@
+use crate::dma;
+use crate::dmamux::DmaMuxIndex;
 use crate::gpio::*;
 use crate::rcc::*;
 use crate::stm32::{SPI1, SPI2};
 use crate::time::Hertz;
+use core::marker::PhantomData;
 use core::ptr;
 pub use hal::spi::{Mode, Phase, Polarity, MODE_0, MODE_1, MODE_2, MODE_3};
 
@@ -14,6 +17,8 @@ pub enum Error {
     ModeFault,
     /// CRC error
     Crc,
+    /// DMA transfer error
+    Dma,
 }
 
 /// A filler type for when the SCK pin is unnecessary
@@ -66,6 +71,16 @@ pub struct Spi<SPI, PINS> {
     pins: PINS,
 }
 
+/// Transmit half of the SPI, as seen by the DMA
+pub struct Tx<'a, SPI> {
+    _spi: PhantomData<&'a mut SPI>,
+}
+
+/// Receive half of the SPI, as seen by the DMA
+pub struct Rx<'a, SPI> {
+    _spi: PhantomData<&'a mut SPI>,
+}
+
 pub trait SpiExt: Sized {
     fn spi<PINS>(self, pins: PINS, mode: Mode, freq: Hertz, rcc: &mut Rcc) -> Spi<Self, PINS>
     where
@@ -73,10 +88,10 @@ pub trait SpiExt: Sized {
 }
 
 macro_rules! spi {
-    ($SPIX:ident, $spiX:ident,
-        sck: [ $(($SCK:ty, $SCK_AF:expr),)+ ],
-        miso: [ $(($MISO:ty, $MISO_AF:expr),)+ ],
-        mosi: [ $(($MOSI:ty, $MOSI_AF:expr),)+ ],
+    ($SPIX:ident, $spiX:ident, $dmamux_rx:ident, $dmamux_tx:ident,
+        sck: [ $(($SCK:ident, $SCK_AF:expr),)+ ],
+        miso: [ $(($MISO:ident, $MISO_AF:expr),)+ ],
+        mosi: [ $(($MOSI:ident, $MOSI_AF:expr),)+ ],
     ) => {
         impl PinSck<$SPIX> for NoSck {
             fn setup(&self) {}
@@ -103,7 +118,7 @@ macro_rules! spi {
         }
 
         $(
-            impl PinSck<$SPIX> for $SCK {
+            impl PinSck<$SPIX> for $SCK<DefaultMode> {
                 fn setup(&self) {
                     self.set_alt_mode($SCK_AF);
                 }
@@ -112,9 +127,19 @@ macro_rules! spi {
                     self.into_analog()
                 }
             }
+
+            impl<OTYPE> PinSck<$SPIX> for $SCK<Alternate<{ $SCK_AF as u8 }, OTYPE>> {
+                fn setup(&self) {}
+
+                fn release(self) -> Self {
+                    self
+                }
+            }
+
+            locked_pin!(impl<OTYPE> PinSck<$SPIX> for $SCK<Alternate<{ $SCK_AF as u8 }, OTYPE>> {});
         )*
         $(
-            impl PinMiso<$SPIX> for $MISO {
+            impl PinMiso<$SPIX> for $MISO<DefaultMode> {
                 fn setup(&self) {
                     self.set_alt_mode($MISO_AF);
                 }
@@ -123,9 +148,19 @@ macro_rules! spi {
                     self.into_analog()
                 }
             }
+
+            impl<OTYPE> PinMiso<$SPIX> for $MISO<Alternate<{ $MISO_AF as u8 }, OTYPE>> {
+                fn setup(&self) {}
+
+                fn release(self) -> Self {
+                    self
+                }
+            }
+
+            locked_pin!(impl<OTYPE> PinMiso<$SPIX> for $MISO<Alternate<{ $MISO_AF as u8 }, OTYPE>> {});
         )*
         $(
-            impl PinMosi<$SPIX> for $MOSI {
+            impl PinMosi<$SPIX> for $MOSI<DefaultMode> {
                 fn setup(&self) {
                     self.set_alt_mode($MOSI_AF);
                 }
@@ -134,6 +169,16 @@ macro_rules! spi {
                     self.into_analog()
                 }
             }
+
+            impl<OTYPE> PinMosi<$SPIX> for $MOSI<Alternate<{ $MOSI_AF as u8 }, OTYPE>> {
+                fn setup(&self) {}
+
+                fn release(self) -> Self {
+                    self
+                }
+            }
+
+            locked_pin!(impl<OTYPE> PinMosi<$SPIX> for $MOSI<Alternate<{ $MOSI_AF as u8 }, OTYPE>> {});
         )*
 
         impl<PINS: Pins<$SPIX>> Spi<$SPIX, PINS> {
@@ -220,6 +265,203 @@ macro_rules! spi {
             pub fn release(self) -> ($SPIX, PINS) {
                 (self.spi, self.pins.release())
             }
+
+            /// Transmit and receive halves of the SPI as DMA targets, to
+            /// use with `dma::Transfer` or `dma::CircBuffer`
+            pub fn split_dma(&mut self) -> (Tx<'_, $SPIX>, Rx<'_, $SPIX>) {
+                self.prepare_dma();
+                (Tx { _spi: PhantomData }, Rx { _spi: PhantomData })
+            }
+
+            /// Write `words` using the DMA `channel`, blocking until done
+            ///
+            /// Slices longer than the 65535 words of a DMA transfer are sent
+            /// in several transfers.
+            pub fn write_dma<CH: dma::Channel>(
+                &mut self,
+                channel: &mut CH,
+                words: &[u8],
+            ) -> Result<(), Error> {
+                if words.is_empty() {
+                    return Ok(());
+                }
+                self.prepare_dma();
+
+                let mut tx = Tx::<$SPIX> { _spi: PhantomData };
+                for chunk in words.chunks(u16::MAX as usize) {
+                    dma::configure(
+                        channel,
+                        &tx,
+                        dma::Direction::FromMemory,
+                        chunk.as_ptr() as u32,
+                        chunk.len(),
+                    );
+                    dma::start(channel, &mut tx);
+                    let result = Self::wait_dma(channel);
+                    dma::stop(channel, &mut tx);
+                    result?;
+                }
+
+                self.wait_idle();
+
+                // Nothing has been read, so drop the received data and the overrun
+                while self.spi.sr.read().frlvl().bits() != 0 {
+                    // NOTE(read_volatile) read only 1 byte, see `FullDuplex::read`
+                    unsafe { ptr::read_volatile(&self.spi.dr as *const _ as *const u8) };
+                }
+                self.spi.sr.read();
+
+                self.check_errors(false)
+            }
+
+            /// Exchange `words` using the DMA channels `rx_channel` and
+            /// `tx_channel`, blocking until done
+            ///
+            /// The words sent are replaced by the words received. Slices
+            /// longer than the 65535 words of a DMA transfer are exchanged
+            /// in several transfers.
+            pub fn transfer_dma<'w, RXCH: dma::Channel, TXCH: dma::Channel>(
+                &mut self,
+                rx_channel: &mut RXCH,
+                tx_channel: &mut TXCH,
+                words: &'w mut [u8],
+            ) -> Result<&'w [u8], Error> {
+                if words.is_empty() {
+                    return Ok(words);
+                }
+                self.prepare_dma();
+
+                let mut rx = Rx::<$SPIX> { _spi: PhantomData };
+                let mut tx = Tx::<$SPIX> { _spi: PhantomData };
+                for chunk in words.chunks_mut(u16::MAX as usize) {
+                    dma::configure(
+                        rx_channel,
+                        &rx,
+                        dma::Direction::FromPeripheral,
+                        chunk.as_mut_ptr() as u32,
+                        chunk.len(),
+                    );
+                    dma::configure(
+                        tx_channel,
+                        &tx,
+                        dma::Direction::FromMemory,
+                        chunk.as_ptr() as u32,
+                        chunk.len(),
+                    );
+
+                    // The reference manual requires RX to be enabled before TX
+                    dma::start(rx_channel, &mut rx);
+                    dma::start(tx_channel, &mut tx);
+
+                    // The last word is received after it has been sent
+                    let result =
+                        Self::wait_dma(tx_channel).and_then(|_| Self::wait_dma(rx_channel));
+                    dma::stop(tx_channel, &mut tx);
+                    dma::stop(rx_channel, &mut rx);
+                    result?;
+                }
+
+                self.wait_idle();
+                self.check_errors(true)?;
+                Ok(words)
+            }
+
+            fn prepare_dma(&mut self) {
+                // The DMA accesses the data register one byte at a time, so
+                // RXNE must be raised for every byte and no packing is used.
+                // LDMA_TX/LDMA_RX only apply to packed transfers of odd length.
+                self.spi.cr2.modify(|_, w| {
+                    w.frxth()
+                        .set_bit()
+                        .ldma_tx()
+                        .clear_bit()
+                        .ldma_rx()
+                        .clear_bit()
+                });
+            }
+
+            fn wait_dma<CH: dma::Channel>(channel: &CH) -> Result<(), Error> {
+                loop {
+                    if channel.event_occurred(dma::Event::TransferError) {
+                        return Err(Error::Dma);
+                    }
+                    if channel.event_occurred(dma::Event::TransferComplete) {
+                        return Ok(());
+                    }
+                }
+            }
+
+            fn wait_idle(&self) {
+                while self.spi.sr.read().ftlvl().bits() != 0 {}
+                while self.spi.sr.read().bsy().bit_is_set() {}
+            }
+
+            fn check_errors(&self, overrun: bool) -> Result<(), Error> {
+                let sr = self.spi.sr.read();
+                if overrun && sr.ovr().bit_is_set() {
+                    Err(Error::Overrun)
+                } else if sr.modf().bit_is_set() {
+                    Err(Error::ModeFault)
+                } else if sr.crcerr().bit_is_set() {
+                    Err(Error::Crc)
+                } else {
+                    Ok(())
+                }
+            }
+        }
+
+        impl dma::Target for Tx<'_, $SPIX> {
+            fn dmamux(&self) -> DmaMuxIndex {
+                DmaMuxIndex::$dmamux_tx
+            }
+
+            fn enable_dma(&mut self) {
+                // NOTE(unsafe) Tx borrows the Spi mutably
+                let spi = unsafe { &(*$SPIX::ptr()) };
+                spi.cr2.modify(|_, w| w.txdmaen().set_bit());
+            }
+
+            fn disable_dma(&mut self) {
+                // NOTE(unsafe) see above
+                let spi = unsafe { &(*$SPIX::ptr()) };
+                spi.cr2.modify(|_, w| w.txdmaen().clear_bit());
+            }
+        }
+
+        unsafe impl dma::TargetAddress<dma::MemoryToPeripheral> for Tx<'_, $SPIX> {
+            type MemSize = u8;
+
+            fn address(&self) -> u32 {
+                // NOTE(unsafe) only the address of the register is taken
+                unsafe { &(*$SPIX::ptr()).dr as *const _ as u32 }
+            }
+        }
+
+        impl dma::Target for Rx<'_, $SPIX> {
+            fn dmamux(&self) -> DmaMuxIndex {
+                DmaMuxIndex::$dmamux_rx
+            }
+
+            fn enable_dma(&mut self) {
+                // NOTE(unsafe) Rx borrows the Spi mutably
+                let spi = unsafe { &(*$SPIX::ptr()) };
+                spi.cr2.modify(|_, w| w.rxdmaen().set_bit());
+            }
+
+            fn disable_dma(&mut self) {
+                // NOTE(unsafe) see above
+                let spi = unsafe { &(*$SPIX::ptr()) };
+                spi.cr2.modify(|_, w| w.rxdmaen().clear_bit());
+            }
+        }
+
+        unsafe impl dma::TargetAddress<dma::PeripheralToMemory> for Rx<'_, $SPIX> {
+            type MemSize = u8;
+
+            fn address(&self) -> u32 {
+                // NOTE(unsafe) only the address of the register is taken
+                unsafe { &(*$SPIX::ptr()).dr as *const _ as u32 }
+            }
         }
 
         impl SpiExt for $SPIX {
@@ -282,53 +524,57 @@ macro_rules! spi {
 spi!(
     SPI1,
     spi1,
+    SPI1_RX,
+    SPI1_TX,
     sck: [
-        (PA1<DefaultMode>, AltFunction::AF0),
-        (PA5<DefaultMode>, AltFunction::AF0),
-        (PB3<DefaultMode>, AltFunction::AF0),
-        (PD8<DefaultMode>, AltFunction::AF1),
+        (PA1, AltFunction::AF0),
+        (PA5, AltFunction::AF0),
+        (PB3, AltFunction::AF0),
+        (PD8, AltFunction::AF1),
     ],
     miso: [
-        (PA6<DefaultMode>, AltFunction::AF0),
-        (PA11<DefaultMode>, AltFunction::AF0),
-        (PB4<DefaultMode>, AltFunction::AF0),
-        (PD5<DefaultMode>, AltFunction::AF1),
+        (PA6, AltFunction::AF0),
+        (PA11, AltFunction::AF0),
+        (PB4, AltFunction::AF0),
+        (PD5, AltFunction::AF1),
     ],
     mosi: [
-        (PA2<DefaultMode>, AltFunction::AF0),
-        (PA7<DefaultMode>, AltFunction::AF0),
-        (PA12<DefaultMode>, AltFunction::AF0),
-        (PB5<DefaultMode>, AltFunction::AF0),
-        (PD6<DefaultMode>, AltFunction::AF1),
+        (PA2, AltFunction::AF0),
+        (PA7, AltFunction::AF0),
+        (PA12, AltFunction::AF0),
+        (PB5, AltFunction::AF0),
+        (PD6, AltFunction::AF1),
     ],
 );
 
 spi!(
     SPI2,
     spi2,
+    SPI2_RX,
+    SPI2_TX,
     sck: [
-        (PA0<DefaultMode>, AltFunction::AF0),
-        (PB8<DefaultMode>, AltFunction::AF1),
-        (PB10<DefaultMode>, AltFunction::AF5),
-        (PB13<DefaultMode>, AltFunction::AF0),
-        (PD1<DefaultMode>, AltFunction::AF1),
+        (PA0, AltFunction::AF0),
+        (PB8, AltFunction::AF1),
+        (PB10, AltFunction::AF5),
+        (PB13, AltFunction::AF0),
+        (PD1, AltFunction::AF1),
     ],
     miso: [
-        (PA3<DefaultMode>, AltFunction::AF0),
-        (PA9<DefaultMode>, AltFunction::AF4),
-        (PB2<DefaultMode>, AltFunction::AF1),
-        (PB6<DefaultMode>, AltFunction::AF4),
-        (PB14<DefaultMode>, AltFunction::AF0),
-        (PC2<DefaultMode>, AltFunction::AF1),
-        (PD3<DefaultMode>, AltFunction::AF1),
+        (PA3, AltFunction::AF0),
+        (PA9, AltFunction::AF4),
+        (PB2, AltFunction::AF1),
+        (PB6, AltFunction::AF4),
+        (PB14, AltFunction::AF0),
+        (PC2, AltFunction::AF1),
+        (PD3, AltFunction::AF1),
     ],
     mosi: [
-        (PA4<DefaultMode>, AltFunction::AF1),
-        (PA10<DefaultMode>, AltFunction::AF0),
-        (PB7<DefaultMode>, AltFunction::AF1),
-        (PB11<DefaultMode>, AltFunction::AF0),
-        (PB15<DefaultMode>, AltFunction::AF0),
-        (PC3<DefaultMode>, AltFunction::AF1),
-        (PD4<DefaultMode>, AltFunction::AF1),
+        (PA4, AltFunction::AF1),
+        (PA10, AltFunction::AF0),
+        (PB7, AltFunction::AF1),
+        (PB11, AltFunction::AF0),
+        (PB15, AltFunction::AF0),
+        (PC3, AltFunction::AF1),
+        (PD4, AltFunction::AF1),
     ],
 );
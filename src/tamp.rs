@@ -0,0 +1,358 @@
+//! Tamper detection
+use crate::exti::LineControl;
+use crate::rcc::Rcc;
+use crate::stm32::TAMP;
+
+/// Tamper input pin
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TamperInput {
+    /// TAMP_IN1 on PC13
+    In1,
+    /// TAMP_IN2 on PA0
+    In2,
+}
+
+/// Internal tamper
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum InternalTamper {
+    /// RTC domain voltage threshold monitoring
+    Itamp1,
+    /// LSE monitoring
+    Itamp3,
+    /// HSE monitoring
+    Itamp4,
+    /// RTC calendar overflow
+    Itamp5,
+    /// Debug access while the readout protection is active
+    Itamp6,
+}
+
+/// Tamper events
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Event {
+    Input(TamperInput),
+    Internal(InternalTamper),
+}
+
+/// Condition which triggers a tamper input
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Trigger {
+    /// Rising edge, requires `Filter::Edge`
+    RisingEdge,
+    /// Falling edge, requires `Filter::Edge`
+    FallingEdge,
+    /// Low level, requires a sampling filter
+    LowLevel,
+    /// High level, requires a sampling filter
+    HighLevel,
+}
+
+/// Tamper input filter
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Filter {
+    /// Trigger on edges, without sampling
+    Edge = 0,
+    /// Trigger on a level stable for 2 consecutive samples
+    Samples2 = 1,
+    /// Trigger on a level stable for 4 consecutive samples
+    Samples4 = 2,
+    /// Trigger on a level stable for 8 consecutive samples
+    Samples8 = 3,
+}
+
+/// Tamper input sampling frequency
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SampleFrequency {
+    Div32768 = 0,
+    Div16384 = 1,
+    Div8192 = 2,
+    Div4096 = 3,
+    Div2048 = 4,
+    Div1024 = 5,
+    Div512 = 6,
+    Div256 = 7,
+}
+
+/// Duration of the precharge of the tamper inputs before sampling
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Precharge {
+    Cycles1 = 0,
+    Cycles2 = 1,
+    Cycles4 = 2,
+    Cycles8 = 3,
+}
+
+/// Configuration shared by the tamper inputs
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Config {
+    filter: Filter,
+    frequency: SampleFrequency,
+    precharge: Option<Precharge>,
+}
+
+impl Config {
+    /// Set the input filter
+    pub fn filter(mut self, filter: Filter) -> Self {
+        self.filter = filter;
+        self
+    }
+
+    /// Set the sampling frequency, as a division of RTCCLK
+    pub fn sample_frequency(mut self, frequency: SampleFrequency) -> Self {
+        self.frequency = frequency;
+        self
+    }
+
+    /// Precharge the inputs with the internal pull up before each sample,
+    /// or disable the pull up with `None`
+    pub fn precharge(mut self, precharge: Option<Precharge>) -> Self {
+        self.precharge = precharge;
+        self
+    }
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            filter: Filter::Edge,
+            frequency: SampleFrequency::Div32768,
+            precharge: Some(Precharge::Cycles1),
+        }
+    }
+}
+
+/// Backup register
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BackupRegister {
+    R0,
+    R1,
+    R2,
+    R3,
+    R4,
+}
+
+/// Backup registers
+///
+/// The five 32-bit registers are kept in Standby and across system resets,
+/// as long as VDD is present. They are erased by tamper events, and by the
+/// backup domain reset of `Rtc::new` when the RTC was not already running
+/// from the same clock source.
+pub struct BackupRegisters {
+    _0: (),
+}
+
+impl BackupRegisters {
+    fn new(rcc: &mut Rcc) -> Self {
+        rcc.apbenr1.modify(|_, w| w.rtcapben().set_bit());
+        rcc.unlock_rtc();
+        BackupRegisters { _0: () }
+    }
+
+    /// Read the backup `register`
+    pub fn read(&self, register: BackupRegister) -> u32 {
+        // NOTE(unsafe) BackupRegisters grants exclusive access to these registers
+        let tamp = unsafe { &(*TAMP::ptr()) };
+        match register {
+            BackupRegister::R0 => tamp.bkp0r.read().bits(),
+            BackupRegister::R1 => tamp.bkp1r.read().bits(),
+            BackupRegister::R2 => tamp.bkp2r.read().bits(),
+            BackupRegister::R3 => tamp.bkp3r.read().bits(),
+            BackupRegister::R4 => tamp.bkp4r.read().bits(),
+        }
+    }
+
+    /// Write `value` to the backup `register`
+    pub fn write(&mut self, register: BackupRegister, value: u32) {
+        // NOTE(unsafe) see above
+        let tamp = unsafe { &(*TAMP::ptr()) };
+        match register {
+            BackupRegister::R0 => tamp.bkp0r.write(|w| unsafe { w.bits(value) }),
+            BackupRegister::R1 => tamp.bkp1r.write(|w| unsafe { w.bits(value) }),
+            BackupRegister::R2 => tamp.bkp2r.write(|w| unsafe { w.bits(value) }),
+            BackupRegister::R3 => tamp.bkp3r.write(|w| unsafe { w.bits(value) }),
+            BackupRegister::R4 => tamp.bkp4r.write(|w| unsafe { w.bits(value) }),
+        }
+    }
+}
+
+/// Tamper detection
+///
+/// A tamper event erases the backup registers unless disabled per input,
+/// and captures a timestamp in the RTC once enabled with
+/// `Rtc::enable_timestamp_on_tamper`.
+pub struct Tamp {
+    rb: TAMP,
+    backup: BackupRegisters,
+}
+
+impl Tamp {
+    pub fn new(tamp: TAMP, config: Config, rcc: &mut Rcc) -> Self {
+        let backup = BackupRegisters::new(rcc);
+        let tamp = Tamp { rb: tamp, backup };
+        tamp.rb.fltcr.write(|w| unsafe {
+            w.tampflt()
+                .bits(config.filter as u8)
+                .tampfreq()
+                .bits(config.frequency as u8)
+                .tampprch()
+                .bits(config.precharge.unwrap_or(Precharge::Cycles1) as u8)
+                .tamppudis()
+                .bit(config.precharge.is_none())
+        });
+        tamp
+    }
+
+    /// Backup registers
+    pub fn backup_registers(&mut self) -> &mut BackupRegisters {
+        &mut self.backup
+    }
+
+    /// Enable the tamper `input`
+    ///
+    /// If `erase_backup` is set, the backup registers are erased when the
+    /// tamper is detected.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the `trigger` does not match the configured filter: edges
+    /// require `Filter::Edge` and levels a sampling filter.
+    pub fn enable_input(&mut self, input: TamperInput, trigger: Trigger, erase_backup: bool) {
+        let edge = self.rb.fltcr.read().tampflt().bits() == Filter::Edge as u8;
+        let trg = match trigger {
+            Trigger::RisingEdge | Trigger::FallingEdge => {
+                assert!(edge);
+                trigger == Trigger::FallingEdge
+            }
+            Trigger::LowLevel | Trigger::HighLevel => {
+                assert!(!edge);
+                trigger == Trigger::HighLevel
+            }
+        };
+        match input {
+            TamperInput::In1 => {
+                self.rb.cr2.modify(|_, w| {
+                    w.tamp1trg()
+                        .bit(trg)
+                        .tamp1noer()
+                        .bit(!erase_backup)
+                        .tamp1msk()
+                        .clear_bit()
+                });
+                self.rb.cr1.modify(|_, w| w.tamp1e().set_bit());
+            }
+            TamperInput::In2 => {
+                self.rb.cr2.modify(|_, w| {
+                    w.tamp2trg()
+                        .bit(trg)
+                        .tamp2noer()
+                        .bit(!erase_backup)
+                        .tamp2msk()
+                        .clear_bit()
+                });
+                self.rb.cr1.modify(|_, w| w.tamp2e().set_bit());
+            }
+        }
+    }
+
+    /// Disable the tamper `input`
+    pub fn disable_input(&mut self, input: TamperInput) {
+        match input {
+            TamperInput::In1 => self.rb.cr1.modify(|_, w| w.tamp1e().clear_bit()),
+            TamperInput::In2 => self.rb.cr1.modify(|_, w| w.tamp2e().clear_bit()),
+        }
+    }
+
+    /// Enable the `internal` tamper, which always erases the backup registers
+    pub fn enable_internal(&mut self, internal: InternalTamper) {
+        self.set_internal(internal, true);
+    }
+
+    /// Disable the `internal` tamper
+    pub fn disable_internal(&mut self, internal: InternalTamper) {
+        self.set_internal(internal, false);
+    }
+
+    fn set_internal(&mut self, internal: InternalTamper, enable: bool) {
+        self.rb.cr1.modify(|_, w| match internal {
+            InternalTamper::Itamp1 => w.itamp1e().bit(enable),
+            InternalTamper::Itamp3 => w.itamp3e().bit(enable),
+            InternalTamper::Itamp4 => w.itamp4e().bit(enable),
+            InternalTamper::Itamp5 => w.itamp5e().bit(enable),
+            InternalTamper::Itamp6 => w.itamp6e().bit(enable),
+        });
+    }
+
+    /// Enable the interrupt of the `event` and its EXTI line, which wakes
+    /// the MCU from Stop and Standby
+    ///
+    /// The line is accessed through the `EXTI` peripheral or its `tamp`
+    /// line handle.
+    pub fn listen<E: LineControl<21>>(&mut self, exti: &mut E, event: Event) {
+        self.set_interrupt(event, true);
+        exti.listen_line();
+    }
+
+    /// Disable the interrupt of the `event`, and the EXTI line once no
+    /// tamper interrupt is enabled anymore
+    pub fn unlisten<E: LineControl<21>>(&mut self, exti: &mut E, event: Event) {
+        self.set_interrupt(event, false);
+        if self.rb.ier.read().bits() == 0 {
+            exti.unlisten_line();
+        }
+    }
+
+    fn set_interrupt(&mut self, event: Event, enable: bool) {
+        self.rb.ier.modify(|_, w| match event {
+            Event::Input(TamperInput::In1) => w.tamp1ie().bit(enable),
+            Event::Input(TamperInput::In2) => w.tamp2ie().bit(enable),
+            Event::Internal(InternalTamper::Itamp1) => w.itamp1ie().bit(enable),
+            Event::Internal(InternalTamper::Itamp3) => w.itamp3ie().bit(enable),
+            Event::Internal(InternalTamper::Itamp4) => w.itamp4ie().bit(enable),
+            Event::Internal(InternalTamper::Itamp5) => w.itamp5ie().bit(enable),
+            Event::Internal(InternalTamper::Itamp6) => w.itamp6ie().bit(enable),
+        });
+    }
+
+    /// Has the `event` been detected?
+    pub fn is_pending(&self, event: Event) -> bool {
+        let sr = self.rb.sr.read();
+        match event {
+            Event::Input(TamperInput::In1) => sr.tamp1f().bit_is_set(),
+            Event::Input(TamperInput::In2) => sr.tamp2f().bit_is_set(),
+            Event::Internal(InternalTamper::Itamp1) => sr.itamp1f().bit_is_set(),
+            Event::Internal(InternalTamper::Itamp3) => sr.itamp3f().bit_is_set(),
+            Event::Internal(InternalTamper::Itamp4) => sr.itamp4f().bit_is_set(),
+            Event::Internal(InternalTamper::Itamp5) => sr.itamp5f().bit_is_set(),
+            Event::Internal(InternalTamper::Itamp6) => sr.itamp6f().bit_is_set(),
+        }
+    }
+
+    /// Clear the flag of the `event`
+    pub fn unpend(&mut self, event: Event) {
+        self.rb.scr.write(|w| match event {
+            Event::Input(TamperInput::In1) => w.ctamp1f().set_bit(),
+            Event::Input(TamperInput::In2) => w.ctamp2f().set_bit(),
+            Event::Internal(InternalTamper::Itamp1) => w.citamp1f().set_bit(),
+            Event::Internal(InternalTamper::Itamp3) => w.citamp3f().set_bit(),
+            Event::Internal(InternalTamper::Itamp4) => w.citamp4f().set_bit(),
+            Event::Internal(InternalTamper::Itamp5) => w.citamp5f().set_bit(),
+            Event::Internal(InternalTamper::Itamp6) => w.citamp6f().set_bit(),
+        });
+    }
+}
+
+pub trait TampExt {
+    fn constrain(self, config: Config, rcc: &mut Rcc) -> Tamp;
+    /// Use the peripheral for its backup registers only
+    fn backup_registers(self, rcc: &mut Rcc) -> BackupRegisters;
+}
+
+impl TampExt for TAMP {
+    fn constrain(self, config: Config, rcc: &mut Rcc) -> Tamp {
+        Tamp::new(self, config, rcc)
+    }
+
+    fn backup_registers(self, rcc: &mut Rcc) -> BackupRegisters {
+        BackupRegisters::new(rcc)
+    }
+}
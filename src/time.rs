@@ -1,3 +1,7 @@
+#[cfg(any(feature = "chrono", feature = "time"))]
+use core::convert::TryFrom;
+use core::ops::Add;
+
 pub use fugit::{
     ExtU32, HertzU32 as Hertz, HoursDurationU32 as Hour, MicrosDurationU32 as MicroSecond,
     MinutesDurationU32 as Minute, RateExtU32, SecsDurationU32 as Second,
@@ -30,6 +34,31 @@ pub struct Month(pub u32);
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub struct Year(pub u32);
 
+/// Number of days
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Days(pub u32);
+
+const SECONDS_PER_DAY: u32 = 86_400;
+
+/// Returns `true` if `year` is a leap year in the Gregorian calendar
+pub fn is_leap_year(year: u32) -> bool {
+    (year.is_multiple_of(4) && !year.is_multiple_of(100)) || year.is_multiple_of(400)
+}
+
+/// Number of days in the `month` (1-12) of `year`
+pub fn days_in_month(year: u32, month: u32) -> u32 {
+    match month {
+        2 if is_leap_year(year) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// Time of day, with `hours` from 0 to 23
+///
+/// `daylight_savings` is a memo of whether the daylight saving time is in
+/// effect, it does not change the time itself.
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub struct Time {
     pub hours: u32,
@@ -39,16 +68,115 @@ pub struct Time {
 }
 
 impl Time {
+    /// Create a time of day
+    ///
+    /// # Panics
+    ///
+    /// Panics if the time is not valid, see `Time::try_new`.
     pub fn new(hours: Hour, minutes: Minute, seconds: Second, daylight_savings: bool) -> Self {
-        Self {
+        Self::try_new(hours, minutes, seconds, daylight_savings).expect("invalid time")
+    }
+
+    /// Create a time of day, or `None` unless `hours` < 24, `minutes` < 60
+    /// and `seconds` < 60
+    pub fn try_new(
+        hours: Hour,
+        minutes: Minute,
+        seconds: Second,
+        daylight_savings: bool,
+    ) -> Option<Self> {
+        let time = Self {
             hours: hours.ticks(),
             minutes: minutes.ticks(),
             seconds: seconds.ticks(),
             daylight_savings,
+        };
+        if time.is_valid() {
+            Some(time)
+        } else {
+            None
+        }
+    }
+
+    /// Create a time of day from a 12-hour clock reading
+    ///
+    /// # Panics
+    ///
+    /// Panics if the time is not valid, see `Time::try_new_12h`.
+    pub fn new_12h(
+        hours: Hour,
+        minutes: Minute,
+        seconds: Second,
+        pm: bool,
+        daylight_savings: bool,
+    ) -> Self {
+        Self::try_new_12h(hours, minutes, seconds, pm, daylight_savings).expect("invalid time")
+    }
+
+    /// Create a time of day from a 12-hour clock reading, or `None` unless
+    /// `hours` is within 1-12, `minutes` < 60 and `seconds` < 60
+    pub fn try_new_12h(
+        hours: Hour,
+        minutes: Minute,
+        seconds: Second,
+        pm: bool,
+        daylight_savings: bool,
+    ) -> Option<Self> {
+        let hours = hours.ticks();
+        if !(1..=12).contains(&hours) {
+            return None;
+        }
+        let hours = hours % 12 + if pm { 12 } else { 0 };
+        Self::try_new(hours.hours(), minutes, seconds, daylight_savings)
+    }
+
+    /// Hours on a 12-hour clock, from 1 to 12
+    pub fn hours_12h(&self) -> u32 {
+        match self.hours % 12 {
+            0 => 12,
+            hours => hours,
+        }
+    }
+
+    /// Returns `true` from noon to midnight
+    pub fn is_pm(&self) -> bool {
+        self.hours >= 12
+    }
+
+    pub fn is_valid(&self) -> bool {
+        self.hours < 24 && self.minutes < 60 && self.seconds < 60
+    }
+
+    /// Number of seconds elapsed since midnight
+    pub fn seconds_since_midnight(&self) -> u32 {
+        self.hours * 3600 + self.minutes * 60 + self.seconds
+    }
+
+    /// Time of day `seconds` after midnight, wrapping around every 24 hours
+    pub fn from_seconds_since_midnight(seconds: u32, daylight_savings: bool) -> Self {
+        let seconds = seconds % SECONDS_PER_DAY;
+        Self {
+            hours: seconds / 3600,
+            minutes: seconds / 60 % 60,
+            seconds: seconds % 60,
+            daylight_savings,
         }
     }
 }
 
+/// Adds a duration to the time of day, wrapping around at midnight
+impl<const NOM: u32, const DENOM: u32> Add<fugit::Duration<u32, NOM, DENOM>> for Time {
+    type Output = Time;
+
+    fn add(self, rhs: fugit::Duration<u32, NOM, DENOM>) -> Time {
+        let seconds = rhs.to_secs() % SECONDS_PER_DAY;
+        Time::from_seconds_since_midnight(
+            self.seconds_since_midnight() + seconds,
+            self.daylight_savings,
+        )
+    }
+}
+
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub struct Date {
     pub day: u32,
@@ -57,15 +185,258 @@ pub struct Date {
 }
 
 impl Date {
+    /// Create a date
+    ///
+    /// # Panics
+    ///
+    /// Panics if the date is not valid, see `Date::try_new`.
     pub fn new(year: Year, month: Month, day: MonthDay) -> Self {
-        Self {
+        Self::try_new(year, month, day).expect("invalid date")
+    }
+
+    /// Create a date, or `None` if it is before 1970 or does not exist
+    pub fn try_new(year: Year, month: Month, day: MonthDay) -> Option<Self> {
+        let date = Self {
             day: day.0,
             month: month.0,
             year: year.0,
+        };
+        if date.is_valid() {
+            Some(date)
+        } else {
+            None
+        }
+    }
+
+    pub fn is_valid(&self) -> bool {
+        self.year >= 1970
+            && (1..=12).contains(&self.month)
+            && self.day >= 1
+            && self.day <= days_in_month(self.year, self.month)
+    }
+
+    /// Day of the week, from 1 (Monday) to 7 (Sunday)
+    pub fn week_day(&self) -> WeekDay {
+        // 1970-01-01 was a Thursday
+        WeekDay((self.days_since_epoch() + 3) % 7 + 1)
+    }
+
+    /// Number of days elapsed since 1970-01-01
+    pub fn days_since_epoch(&self) -> u32 {
+        // Shift the year to start in March, so the leap day is the last one
+        let (year, month) = if self.month > 2 {
+            (self.year, self.month - 3)
+        } else {
+            (self.year - 1, self.month + 9)
+        };
+        let era = year / 400;
+        let year_of_era = year % 400;
+        let day_of_year = (153 * month + 2) / 5 + self.day - 1;
+        let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+        era * 146_097 + day_of_era - 719_468
+    }
+
+    /// Date `days` after 1970-01-01
+    pub fn from_days_since_epoch(days: u32) -> Self {
+        let days = days + 719_468;
+        let era = days / 146_097;
+        let day_of_era = days % 146_097;
+        let year_of_era =
+            (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+        let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+        let month = (5 * day_of_year + 2) / 153;
+        let day = day_of_year - (153 * month + 2) / 5 + 1;
+        let (year, month) = if month < 10 {
+            (era * 400 + year_of_era, month + 3)
+        } else {
+            (era * 400 + year_of_era + 1, month - 9)
+        };
+        Self { day, month, year }
+    }
+
+    /// Seconds elapsed since the Unix epoch at `time` on this date
+    pub fn to_unix_timestamp(&self, time: &Time) -> u32 {
+        self.days_since_epoch() * SECONDS_PER_DAY + time.seconds_since_midnight()
+    }
+
+    /// Date and time `timestamp` seconds after the Unix epoch
+    pub fn from_unix_timestamp(timestamp: u32) -> (Self, Time) {
+        (
+            Self::from_days_since_epoch(timestamp / SECONDS_PER_DAY),
+            Time::from_seconds_since_midnight(timestamp, false),
+        )
+    }
+}
+
+impl Add<Days> for Date {
+    type Output = Date;
+
+    fn add(self, rhs: Days) -> Date {
+        Date::from_days_since_epoch(self.days_since_epoch() + rhs.0)
+    }
+}
+
+/// Date and time of day
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DateTime {
+    pub date: Date,
+    pub time: Time,
+}
+
+impl DateTime {
+    pub fn new(date: Date, time: Time) -> Self {
+        Self { date, time }
+    }
+}
+
+/// Adds a duration to the date and time, carrying whole days into the date
+impl<const NOM: u32, const DENOM: u32> Add<fugit::Duration<u32, NOM, DENOM>> for DateTime {
+    type Output = DateTime;
+
+    fn add(self, rhs: fugit::Duration<u32, NOM, DENOM>) -> DateTime {
+        let seconds = self.time.seconds_since_midnight() + rhs.to_secs() % SECONDS_PER_DAY;
+        let days = rhs.to_secs() / SECONDS_PER_DAY + seconds / SECONDS_PER_DAY;
+        DateTime {
+            date: self.date + Days(days),
+            time: Time::from_seconds_since_midnight(seconds, self.time.daylight_savings),
         }
     }
 }
 
+#[cfg(feature = "chrono")]
+impl TryFrom<Date> for chrono::NaiveDate {
+    type Error = ();
+
+    fn try_from(date: Date) -> Result<Self, ()> {
+        chrono::NaiveDate::from_ymd_opt(date.year as i32, date.month, date.day).ok_or(())
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl TryFrom<chrono::NaiveDate> for Date {
+    type Error = ();
+
+    fn try_from(date: chrono::NaiveDate) -> Result<Self, ()> {
+        use chrono::Datelike;
+        let year = u32::try_from(date.year()).map_err(|_| ())?;
+        Date::try_new(year.year(), date.month().month(), date.day().day()).ok_or(())
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl TryFrom<Time> for chrono::NaiveTime {
+    type Error = ();
+
+    fn try_from(time: Time) -> Result<Self, ()> {
+        chrono::NaiveTime::from_hms_opt(time.hours, time.minutes, time.seconds).ok_or(())
+    }
+}
+
+/// Leap seconds are rounded down, `daylight_savings` is cleared
+#[cfg(feature = "chrono")]
+impl From<chrono::NaiveTime> for Time {
+    fn from(time: chrono::NaiveTime) -> Self {
+        use chrono::Timelike;
+        Time::from_seconds_since_midnight(time.num_seconds_from_midnight(), false)
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl TryFrom<DateTime> for chrono::NaiveDateTime {
+    type Error = ();
+
+    fn try_from(date_time: DateTime) -> Result<Self, ()> {
+        Ok(chrono::NaiveDateTime::new(
+            chrono::NaiveDate::try_from(date_time.date)?,
+            chrono::NaiveTime::try_from(date_time.time)?,
+        ))
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl TryFrom<chrono::NaiveDateTime> for DateTime {
+    type Error = ();
+
+    fn try_from(date_time: chrono::NaiveDateTime) -> Result<Self, ()> {
+        Ok(DateTime {
+            date: Date::try_from(date_time.date())?,
+            time: Time::from(date_time.time()),
+        })
+    }
+}
+
+#[cfg(feature = "time")]
+impl TryFrom<Date> for ::time::Date {
+    type Error = ();
+
+    fn try_from(date: Date) -> Result<Self, ()> {
+        let month = ::time::Month::try_from(date.month as u8).map_err(|_| ())?;
+        ::time::Date::from_calendar_date(date.year as i32, month, date.day as u8).map_err(|_| ())
+    }
+}
+
+#[cfg(feature = "time")]
+impl TryFrom<::time::Date> for Date {
+    type Error = ();
+
+    fn try_from(date: ::time::Date) -> Result<Self, ()> {
+        let year = u32::try_from(date.year()).map_err(|_| ())?;
+        Date::try_new(
+            year.year(),
+            (date.month() as u32).month(),
+            (date.day() as u32).day(),
+        )
+        .ok_or(())
+    }
+}
+
+#[cfg(feature = "time")]
+impl TryFrom<Time> for ::time::Time {
+    type Error = ();
+
+    fn try_from(time: Time) -> Result<Self, ()> {
+        ::time::Time::from_hms(time.hours as u8, time.minutes as u8, time.seconds as u8)
+            .map_err(|_| ())
+    }
+}
+
+/// Fractions of a second are truncated, `daylight_savings` is cleared
+#[cfg(feature = "time")]
+impl From<::time::Time> for Time {
+    fn from(time: ::time::Time) -> Self {
+        Time {
+            hours: time.hour() as u32,
+            minutes: time.minute() as u32,
+            seconds: time.second() as u32,
+            daylight_savings: false,
+        }
+    }
+}
+
+#[cfg(feature = "time")]
+impl TryFrom<DateTime> for ::time::PrimitiveDateTime {
+    type Error = ();
+
+    fn try_from(date_time: DateTime) -> Result<Self, ()> {
+        Ok(::time::PrimitiveDateTime::new(
+            ::time::Date::try_from(date_time.date)?,
+            ::time::Time::try_from(date_time.time)?,
+        ))
+    }
+}
+
+#[cfg(feature = "time")]
+impl TryFrom<::time::PrimitiveDateTime> for DateTime {
+    type Error = ();
+
+    fn try_from(date_time: ::time::PrimitiveDateTime) -> Result<Self, ()> {
+        Ok(DateTime {
+            date: Date::try_from(date_time.date())?,
+            time: Time::from(date_time.time()),
+        })
+    }
+}
+
 pub trait U32Ext {
     /// Wrap in `Bps`
     fn bps(self) -> Bps;
@@ -78,6 +449,9 @@ pub trait U32Ext {
 
     /// Year
     fn year(self) -> Year;
+
+    /// Number of days
+    fn days(self) -> Days;
 }
 
 impl U32Ext for u32 {
@@ -96,6 +470,10 @@ impl U32Ext for u32 {
     fn year(self) -> Year {
         Year(self)
     }
+
+    fn days(self) -> Days {
+        Days(self)
+    }
 }
 
 pub fn duration(hz: Hertz, cycles: u32) -> MicroSecond {
@@ -112,3 +490,118 @@ pub fn cycles(ms: MicroSecond, clk: Hertz) -> u32 {
     let cycles = clk.saturating_mul(period) / 1_000_000_u64;
     cycles as u32
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn leap_years() {
+        assert!(is_leap_year(1972));
+        assert!(is_leap_year(2000));
+        assert!(is_leap_year(2024));
+        assert!(!is_leap_year(1970));
+        assert!(!is_leap_year(2023));
+        assert!(!is_leap_year(2100));
+    }
+
+    #[test]
+    fn month_lengths() {
+        let lengths = [31, 28, 31, 30, 31, 30, 31, 31, 30, 31, 30, 31];
+        for (month, length) in (1..=12).zip(lengths) {
+            assert_eq!(days_in_month(2023, month), length);
+        }
+        assert_eq!(days_in_month(2024, 2), 29);
+        assert_eq!(days_in_month(2100, 2), 28);
+    }
+
+    #[test]
+    fn week_days() {
+        assert_eq!(
+            Date::new(1970.year(), 1.month(), 1.day()).week_day(),
+            WeekDay(4)
+        );
+        assert_eq!(
+            Date::new(2000.year(), 1.month(), 1.day()).week_day(),
+            WeekDay(6)
+        );
+        assert_eq!(
+            Date::new(2000.year(), 2.month(), 29.day()).week_day(),
+            WeekDay(2)
+        );
+        assert_eq!(
+            Date::new(2024.year(), 3.month(), 3.day()).week_day(),
+            WeekDay(7)
+        );
+        assert_eq!(
+            Date::new(2099.year(), 12.month(), 31.day()).week_day(),
+            WeekDay(4)
+        );
+    }
+
+    #[test]
+    fn unix_timestamps() {
+        let date = Date::new(2024.year(), 2.month(), 29.day());
+        let time = Time::new(13.hours(), 37.minutes(), 42.secs(), false);
+        assert_eq!(date.to_unix_timestamp(&time), 1_709_213_862);
+        assert_eq!(Date::from_unix_timestamp(1_709_213_862), (date, time));
+
+        for timestamp in (0..u32::MAX - SECONDS_PER_DAY).step_by(7_777_777) {
+            let (date, time) = Date::from_unix_timestamp(timestamp);
+            assert!(date.is_valid() && time.is_valid());
+            assert_eq!(date.to_unix_timestamp(&time), timestamp);
+        }
+    }
+
+    #[test]
+    fn date_time_carry() {
+        let date_time = DateTime::new(
+            Date::new(2023.year(), 12.month(), 31.day()),
+            Time::new(23.hours(), 59.minutes(), 30.secs(), false),
+        );
+        let later = date_time + Second::secs(45);
+        assert_eq!(later.date, Date::new(2024.year(), 1.month(), 1.day()));
+        assert_eq!(
+            later.time,
+            Time::new(0.hours(), 0.minutes(), 15.secs(), false)
+        );
+
+        let later = date_time + Hour::hours(49);
+        assert_eq!(later.date, Date::new(2024.year(), 1.month(), 3.day()));
+        assert_eq!(
+            later.time,
+            Time::new(0.hours(), 59.minutes(), 30.secs(), false)
+        );
+    }
+
+    #[cfg(feature = "chrono")]
+    #[test]
+    fn chrono_conversions() {
+        let date_time = DateTime::new(
+            Date::new(2024.year(), 2.month(), 29.day()),
+            Time::new(13.hours(), 37.minutes(), 42.secs(), false),
+        );
+        let naive = chrono::NaiveDateTime::try_from(date_time).unwrap();
+        assert_eq!(naive.and_utc().timestamp(), 1_709_213_862);
+        assert_eq!(DateTime::try_from(naive), Ok(date_time));
+        assert!(Date::try_from(chrono::NaiveDate::from_ymd_opt(1969, 12, 31).unwrap()).is_err());
+    }
+
+    #[cfg(feature = "time")]
+    #[test]
+    fn time_conversions() {
+        let date_time = DateTime::new(
+            Date::new(2024.year(), 2.month(), 29.day()),
+            Time::new(13.hours(), 37.minutes(), 42.secs(), false),
+        );
+        let primitive = ::time::PrimitiveDateTime::try_from(date_time).unwrap();
+        assert_eq!(primitive.assume_utc().unix_timestamp(), 1_709_213_862);
+        assert_eq!(DateTime::try_from(primitive), Ok(date_time));
+        let invalid = Date {
+            day: 30,
+            month: 2,
+            year: 2024,
+        };
+        assert!(::time::Date::try_from(invalid).is_err());
+    }
+}